@@ -0,0 +1,203 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::Claim;
+
+/// Undirected graph between claims that share at least one square inch of fabric. Every edge
+/// carries the area of the overlap between the two claims.
+pub struct ConflictGraph {
+    /// claim id -> (conflicting claim id -> overlap area)
+    edges: BTreeMap<u32, BTreeMap<u32, u32>>,
+}
+
+/// A group of claims connected to each other through overlaps
+pub struct Component {
+    pub ids: Vec<u32>,
+    pub pairs: usize,
+    pub contested_area: u32,
+}
+
+/// How badly a single claim is contested
+pub struct Contention {
+    pub id: u32,
+    pub conflicts: usize,
+    pub overlap_area: u32,
+    pub contested_area: u32,
+}
+
+impl ConflictGraph {
+    pub fn new(claims: &[Claim]) -> Self {
+        let mut edges: BTreeMap<u32, BTreeMap<u32, u32>> = BTreeMap::new();
+        for claim in claims {
+            edges.entry(claim.id).or_default();
+        }
+
+        // sweep from left to right so that only claims whose horizontal spans meet are compared
+        let mut sorted = claims.iter().collect::<Vec<&Claim>>();
+        sorted.sort_by_key(|claim| claim.left_pad);
+
+        for (idx, claim) in sorted.iter().enumerate() {
+            for other in &sorted[idx + 1..] {
+                if other.left_pad >= claim.right() {
+                    break;
+                }
+
+                let area = claim.overlap(other);
+                if area > 0 {
+                    edges.entry(claim.id).or_default().insert(other.id, area);
+                    edges.entry(other.id).or_default().insert(claim.id, area);
+                }
+            }
+        }
+
+        ConflictGraph { edges }
+    }
+
+    /// every conflicting pair once, as (smaller id, larger id, overlap area)
+    pub fn pairs(&self) -> impl Iterator<Item = (u32, u32, u32)> + '_ {
+        self.edges.iter().flat_map(|(&id, neighbours)| {
+            neighbours
+                .iter()
+                .filter(move |(&other, _)| id < other)
+                .map(move |(&other, &area)| (id, other, area))
+        })
+    }
+
+    /// Connected components with at least two claims, largest first. Claims that overlap nothing
+    /// are left out.
+    pub fn components(&self, grid: &HashMap<(u32, u32), u32>, claims: &[Claim]) -> Vec<Component> {
+        let by_id = claims
+            .iter()
+            .map(|claim| (claim.id, claim))
+            .collect::<HashMap<u32, &Claim>>();
+
+        let mut seen: BTreeMap<u32, bool> = self.edges.keys().map(|&id| (id, false)).collect();
+        let mut components = Vec::new();
+
+        for (&start, neighbours) in &self.edges {
+            if seen[&start] || neighbours.is_empty() {
+                continue;
+            }
+
+            let mut ids = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            seen.insert(start, true);
+            while let Some(id) = queue.pop_front() {
+                ids.push(id);
+                for &next in self.edges[&id].keys() {
+                    if !seen[&next] {
+                        seen.insert(next, true);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            ids.sort();
+
+            let pairs = ids.iter().map(|id| self.edges[id].len()).sum::<usize>() / 2;
+
+            // a square shared by several claims of the component is counted once
+            let mut cells: HashSet<(u32, u32)> = HashSet::new();
+            for id in &ids {
+                for cell in by_id[id].iter() {
+                    if grid.get(&cell).copied().unwrap_or(0) > 1 {
+                        cells.insert(cell);
+                    }
+                }
+            }
+
+            components.push(Component {
+                ids,
+                pairs,
+                contested_area: cells.len() as u32,
+            });
+        }
+
+        components.sort_by(|a, b| b.ids.len().cmp(&a.ids.len()).then(a.ids[0].cmp(&b.ids[0])));
+        components
+    }
+
+    /// Claims ranked by the number of claims they overlap, then by how much of their own area is
+    /// contested.
+    pub fn ranking(&self, grid: &HashMap<(u32, u32), u32>, claims: &[Claim]) -> Vec<Contention> {
+        let mut ranking = claims
+            .iter()
+            .filter(|claim| !self.edges[&claim.id].is_empty())
+            .map(|claim| Contention {
+                id: claim.id,
+                conflicts: self.edges[&claim.id].len(),
+                overlap_area: self.edges[&claim.id].values().sum(),
+                contested_area: claim
+                    .iter()
+                    .filter(|cell| grid.get(cell).copied().unwrap_or(0) > 1)
+                    .count() as u32,
+            })
+            .collect::<Vec<Contention>>();
+
+        ranking.sort_by(|a, b| {
+            b.conflicts
+                .cmp(&a.conflicts)
+                .then(b.contested_area.cmp(&a.contested_area))
+                .then(a.id.cmp(&b.id))
+        });
+        ranking
+    }
+
+    /// graphviz representation, edges labelled with the overlap area
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph conflicts {\n");
+        for (id, other, area) in self.pairs() {
+            dot.push_str(&format!("    {id} -- {other} [label=\"{area}\"];\n"));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+pub fn report(
+    claims: &[Claim],
+    grid: &HashMap<(u32, u32), u32>,
+    top: usize,
+    dot: bool,
+) -> crate::Result<()> {
+    let graph = ConflictGraph::new(claims);
+
+    if dot {
+        print!("{}", graph.to_dot());
+        return Ok(());
+    }
+
+    let components = graph.components(grid, claims);
+    let isolated = claims.len() - components.iter().map(|c| c.ids.len()).sum::<usize>();
+
+    println!("conflicting pairs: {}", graph.pairs().count());
+    println!(
+        "components: {} ({} claims overlap nothing)",
+        components.len(),
+        isolated
+    );
+    for (idx, component) in components.iter().enumerate() {
+        let ids = component
+            .ids
+            .iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<String>>()
+            .join(" ");
+        println!(
+            "  {:>4}: {} claims, {} pairs, {} contested: {}",
+            idx + 1,
+            component.ids.len(),
+            component.pairs,
+            component.contested_area,
+            ids
+        );
+    }
+
+    println!("most contested:");
+    for contention in graph.ranking(grid, claims).iter().take(top) {
+        println!(
+            "  #{:<5} conflicts: {:<3} overlap: {:<5} contested: {}",
+            contention.id, contention.conflicts, contention.overlap_area, contention.contested_area
+        );
+    }
+
+    Ok(())
+}
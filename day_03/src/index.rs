@@ -0,0 +1,254 @@
+use crate::Claim;
+
+/// maximum number of entries held by a single node of the tree
+const FANOUT: usize = 8;
+
+/// Half open rectangle of fabric, `left..right` by `top..bottom`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl Bounds {
+    fn contains(&self, (x, y): (u32, u32)) -> bool {
+        self.left <= x && x < self.right && self.top <= y && y < self.bottom
+    }
+
+    fn intersects(&self, other: &Bounds) -> bool {
+        self.left < other.right
+            && other.left < self.right
+            && self.top < other.bottom
+            && other.top < self.bottom
+    }
+
+    /// smallest rectangle covering both
+    fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// twice the center, good enough for ordering. In u64 since the sides can reach u32::MAX.
+    fn center(&self) -> (u64, u64) {
+        (
+            self.left as u64 + self.right as u64,
+            self.top as u64 + self.bottom as u64,
+        )
+    }
+}
+
+struct Node {
+    bounds: Bounds,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    /// claims as (bounds, id)
+    Leaf(Vec<(Bounds, u32)>),
+    /// indices into `ClaimIndex::nodes`
+    Branch(Vec<usize>),
+}
+
+/// Static R-tree over the claims, bulk loaded with sort-tile-recursive packing. Built once after
+/// parsing, it answers point and rectangle lookups without walking the whole fabric.
+pub struct ClaimIndex {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+impl ClaimIndex {
+    pub fn new(claims: &[Claim]) -> Self {
+        let mut nodes = Vec::new();
+        let entries = claims
+            .iter()
+            .map(|claim| (claim.bounds(), claim.id))
+            .collect::<Vec<(Bounds, u32)>>();
+
+        if entries.is_empty() {
+            return ClaimIndex { nodes, root: None };
+        }
+
+        // bottom level: pack the claims themselves into leaves
+        let mut level = Vec::new();
+        for chunk in tile(entries, |entry| entry.0) {
+            let bounds = chunk
+                .iter()
+                .skip(1)
+                .fold(chunk[0].0, |acc, entry| acc.union(&entry.0));
+            nodes.push(Node {
+                bounds,
+                kind: NodeKind::Leaf(chunk),
+            });
+            level.push(nodes.len() - 1);
+        }
+
+        // keep packing the previous level until a single root remains
+        while level.len() > 1 {
+            let mut next_level = Vec::new();
+            let children = level
+                .iter()
+                .map(|&idx| (nodes[idx].bounds, idx))
+                .collect::<Vec<(Bounds, usize)>>();
+            for chunk in tile(children, |child| child.0) {
+                let bounds = chunk
+                    .iter()
+                    .skip(1)
+                    .fold(chunk[0].0, |acc, child| acc.union(&child.0));
+                nodes.push(Node {
+                    bounds,
+                    kind: NodeKind::Branch(chunk.into_iter().map(|(_, idx)| idx).collect()),
+                });
+                next_level.push(nodes.len() - 1);
+            }
+            level = next_level;
+        }
+
+        ClaimIndex {
+            root: Some(level[0]),
+            nodes,
+        }
+    }
+
+    /// ids of every claim covering square (x, y), sorted
+    pub fn at(&self, point: (u32, u32)) -> Vec<u32> {
+        self.search(|bounds| bounds.contains(point))
+    }
+
+    /// ids of every claim sharing at least one square with `area`, sorted
+    pub fn intersecting(&self, area: &Bounds) -> Vec<u32> {
+        self.search(|bounds| bounds.intersects(area))
+    }
+
+    fn search(&self, matches: impl Fn(&Bounds) -> bool) -> Vec<u32> {
+        let mut found = Vec::new();
+        let mut pending = self.root.into_iter().collect::<Vec<usize>>();
+
+        while let Some(idx) = pending.pop() {
+            let node = &self.nodes[idx];
+            if !matches(&node.bounds) {
+                continue;
+            }
+            match &node.kind {
+                NodeKind::Leaf(entries) => found.extend(
+                    entries
+                        .iter()
+                        .filter(|(bounds, _)| matches(bounds))
+                        .map(|(_, id)| *id),
+                ),
+                NodeKind::Branch(children) => pending.extend(children),
+            }
+        }
+
+        found.sort();
+        found
+    }
+}
+
+/// Sort-tile-recursive grouping: sort by x, cut into vertical slices, sort every slice by y and
+/// cut it into groups of at most FANOUT items.
+fn tile<T>(mut items: Vec<T>, bounds: impl Fn(&T) -> Bounds) -> Vec<Vec<T>> {
+    let group_count = items.len().div_ceil(FANOUT);
+    let slice_count = (group_count as f64).sqrt().ceil() as usize;
+    let slice_size = slice_count * FANOUT;
+
+    items.sort_by_key(|item| bounds(item).center().0);
+
+    let mut groups = Vec::with_capacity(group_count);
+    while !items.is_empty() {
+        let rest = items.split_off(slice_size.min(items.len()));
+        let mut slice = std::mem::replace(&mut items, rest);
+        slice.sort_by_key(|item| bounds(item).center().1);
+        while !slice.is_empty() {
+            let rest = slice.split_off(FANOUT.min(slice.len()));
+            groups.push(std::mem::replace(&mut slice, rest));
+        }
+    }
+
+    groups
+}
+
+/// parse "x,y" as a square of fabric
+pub fn parse_point(value: &str) -> std::result::Result<(u32, u32), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected x,y but got '{value}'"))?;
+    let x = x
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("bad x '{x}': {e}"))?;
+    let y = y
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("bad y '{y}': {e}"))?;
+    Ok((x, y))
+}
+
+/// parse "x,y:wxh", the same layout as the position and size of a claim
+pub fn parse_bounds(value: &str) -> std::result::Result<Bounds, String> {
+    let (corner, size) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected x,y:wxh but got '{value}'"))?;
+    let (left, top) = parse_point(corner)?;
+    let (width, height) = size
+        .trim()
+        .split_once('x')
+        .ok_or_else(|| format!("expected wxh but got '{size}'"))?;
+    let width = width
+        .parse::<u32>()
+        .map_err(|e| format!("bad width '{width}': {e}"))?;
+    let height = height
+        .parse::<u32>()
+        .map_err(|e| format!("bad height '{height}': {e}"))?;
+    let right = left
+        .checked_add(width)
+        .ok_or_else(|| format!("'{value}' reaches past {}", u32::MAX))?;
+    let bottom = top
+        .checked_add(height)
+        .ok_or_else(|| format!("'{value}' reaches past {}", u32::MAX))?;
+    Ok(Bounds {
+        left,
+        top,
+        right,
+        bottom,
+    })
+}
+
+pub fn query(claims: &[Claim], at: Option<(u32, u32)>, area: Option<Bounds>) -> crate::Result<()> {
+    if at.is_none() && area.is_none() {
+        return Err("query needs --at and/or --rect".into());
+    }
+
+    let index = ClaimIndex::new(claims);
+    let format = |ids: Vec<u32>| {
+        ids.iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    if let Some((x, y)) = at {
+        let ids = index.at((x, y));
+        println!("{},{}: {} claims: {}", x, y, ids.len(), format(ids));
+    }
+
+    if let Some(area) = area {
+        let ids = index.intersecting(&area);
+        println!(
+            "{},{}: {}x{}: {} claims: {}",
+            area.left,
+            area.top,
+            area.right - area.left,
+            area.bottom - area.top,
+            ids.len(),
+            format(ids)
+        );
+    }
+
+    Ok(())
+}
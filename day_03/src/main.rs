@@ -1,7 +1,86 @@
-use std::{collections::HashMap, fs};
+use clap::{Parser, Subcommand};
+use index::Bounds;
+use std::{collections::HashMap, fmt, fs, num::ParseIntError, str::FromStr};
+
+mod conflict;
+mod index;
+mod render;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+#[derive(Parser, Debug)]
+struct Args {
+    /// Name of input file
+    input: String,
+
+    /// Size of the fabric as wxh, claims reaching past it are rejected
+    #[arg(long, value_parser = parse_size)]
+    fabric: Option<(u32, u32)>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Report which claims overlap which, grouped into connected components
+    Conflicts {
+        /// Number of most contested claims to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Print the conflict graph in graphviz DOT format instead
+        #[arg(long)]
+        dot: bool,
+    },
+
+    /// List the claims covering a square and/or intersecting a rectangle
+    Query {
+        /// Square of fabric as x,y
+        #[arg(long, value_parser = index::parse_point)]
+        at: Option<(u32, u32)>,
+
+        /// Rectangle of fabric as x,y:wxh
+        #[arg(long, value_parser = index::parse_bounds)]
+        rect: Option<Bounds>,
+    },
+
+    /// Draw the fabric as a heatmap of claim counts
+    Render {
+        /// Output image, either .gif or .ppm
+        #[arg(short, long, default_value = "fabric.gif")]
+        output: String,
+
+        /// Pixels per square inch
+        #[arg(long, default_value_t = 1)]
+        scale: u32,
+
+        /// Square inches per pixel along each side, for fabrics too big to draw one to one
+        #[arg(long, default_value_t = 1)]
+        bin: u32,
+    },
+
+    /// Print every claim back in the canonical `#id @ x,y: wxh` form
+    Format,
+}
+
+/// parse "wxh"
+fn parse_size(value: &str) -> std::result::Result<(u32, u32), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("expected wxh but got '{value}'"))?;
+    let width = width
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("bad width '{width}': {e}"))?;
+    let height = height
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("bad height '{height}': {e}"))?;
+    Ok((width, height))
+}
+
+#[derive(Debug)]
 struct Claim {
     id: u32,
     left_pad: u32,
@@ -11,18 +90,35 @@ struct Claim {
 }
 
 impl Claim {
-    fn new(line: &str) -> Self {
-        let tokens = line[1..]
-            .split(['@', ',', ':', 'x'])
-            .map(|sub_str| sub_str.trim().parse::<u32>().unwrap())
-            .collect::<Vec<u32>>();
-
-        Claim {
-            id: tokens[0],
-            left_pad: tokens[1],
-            top_pad: tokens[2],
-            width: tokens[3],
-            height: tokens[4],
+    /// first column to the right of the claim
+    fn right(&self) -> u32 {
+        self.left_pad + self.width
+    }
+
+    /// first row below the claim
+    fn bottom(&self) -> u32 {
+        self.top_pad + self.height
+    }
+
+    /// number of square inches shared with another claim
+    fn overlap(&self, other: &Claim) -> u32 {
+        let width = self
+            .right()
+            .min(other.right())
+            .saturating_sub(self.left_pad.max(other.left_pad));
+        let height = self
+            .bottom()
+            .min(other.bottom())
+            .saturating_sub(self.top_pad.max(other.top_pad));
+        width * height
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            left: self.left_pad,
+            top: self.top_pad,
+            right: self.right(),
+            bottom: self.bottom(),
         }
     }
 
@@ -38,6 +134,140 @@ impl Claim {
     }
 }
 
+/// Why a claim was rejected
+#[derive(Debug)]
+enum ClaimError {
+    /// the line does not look like `#id @ x,y: wxh`
+    Format(String),
+    /// one of the five numbers could not be read
+    Number(String, ParseIntError),
+    /// the claim reaches past u32::MAX
+    TooLarge,
+    ZeroArea(u32),
+    /// id and the line it was first claimed on
+    Duplicate(u32, usize),
+    /// id and the fabric size it does not fit in
+    OutsideFabric(u32, (u32, u32)),
+}
+
+impl fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaimError::Format(line) => write!(f, "expected '#id @ x,y: wxh' but got '{line}'"),
+            ClaimError::Number(token, e) => write!(f, "could not read '{token}': {e}"),
+            ClaimError::TooLarge => write!(f, "claim does not fit in u32 coordinates"),
+            ClaimError::ZeroArea(id) => write!(f, "claim #{id} covers no fabric"),
+            ClaimError::Duplicate(id, first) => {
+                write!(f, "claim #{id} was already made on line {first}")
+            }
+            ClaimError::OutsideFabric(id, (width, height)) => {
+                write!(f, "claim #{id} reaches outside the {width}x{height} fabric")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClaimError {}
+
+impl FromStr for Claim {
+    type Err = ClaimError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let malformed = || ClaimError::Format(s.to_string());
+        let number = |token: &str| {
+            token
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| ClaimError::Number(token.trim().to_string(), e))
+        };
+
+        let rest = s.trim().strip_prefix('#').ok_or_else(malformed)?;
+        let (id, rest) = rest.split_once('@').ok_or_else(malformed)?;
+        let (corner, size) = rest.split_once(':').ok_or_else(malformed)?;
+        let (left_pad, top_pad) = corner.split_once(',').ok_or_else(malformed)?;
+        let (width, height) = size.split_once('x').ok_or_else(malformed)?;
+
+        let claim = Claim {
+            id: number(id)?,
+            left_pad: number(left_pad)?,
+            top_pad: number(top_pad)?,
+            width: number(width)?,
+            height: number(height)?,
+        };
+
+        if claim.left_pad.checked_add(claim.width).is_none()
+            || claim.top_pad.checked_add(claim.height).is_none()
+        {
+            return Err(ClaimError::TooLarge);
+        }
+
+        Ok(claim)
+    }
+}
+
+impl fmt::Display for Claim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} @ {},{}: {}x{}",
+            self.id, self.left_pad, self.top_pad, self.width, self.height
+        )
+    }
+}
+
+/// Parse every non empty line and check the claims against each other and the fabric. Problems
+/// are returned with the line number they were found on, nothing is dropped silently.
+fn parse_claims(
+    input: &str,
+    fabric: Option<(u32, u32)>,
+) -> std::result::Result<Vec<Claim>, Vec<(usize, ClaimError)>> {
+    let mut claims: Vec<Claim> = Vec::new();
+    let mut errors: Vec<(usize, ClaimError)> = Vec::new();
+    let mut first_seen: HashMap<u32, usize> = HashMap::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line_number = idx + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let claim = match Claim::from_str(line) {
+            Ok(claim) => claim,
+            Err(e) => {
+                errors.push((line_number, e));
+                continue;
+            }
+        };
+
+        if let Some(&first) = first_seen.get(&claim.id) {
+            errors.push((line_number, ClaimError::Duplicate(claim.id, first)));
+        } else {
+            first_seen.insert(claim.id, line_number);
+        }
+
+        if claim.width == 0 || claim.height == 0 {
+            errors.push((line_number, ClaimError::ZeroArea(claim.id)));
+        }
+
+        if let Some((width, height)) = fabric {
+            if claim.right() > width || claim.bottom() > height {
+                errors.push((
+                    line_number,
+                    ClaimError::OutsideFabric(claim.id, (width, height)),
+                ));
+            }
+        }
+
+        claims.push(claim);
+    }
+
+    if errors.is_empty() {
+        Ok(claims)
+    } else {
+        Err(errors)
+    }
+}
+
 struct Rectangle {
     width: u32,
     height: u32,
@@ -62,7 +292,7 @@ impl Iterator for Rectangle {
                 self.current_x += 1;
             }
 
-            return Some(returnable);
+            Some(returnable)
         } else {
             None
         }
@@ -70,44 +300,57 @@ impl Iterator for Rectangle {
 }
 
 fn main() -> Result<()> {
-    let args = std::env::args().collect::<Vec<String>>();
-
-    if args.len() != 2 {
-        eprintln!("usage: {} <input_filename>", args[0]);
-        std::process::exit(1);
-    }
+    let args = Args::parse();
 
-    let filename = args[1].as_str();
+    let input = fs::read_to_string(args.input.as_str())?;
 
-    let input = fs::read_to_string(filename)?;
-
-    let input_lines = input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<&str>>();
-    let mut claims: Vec<Claim> = Vec::with_capacity(input_lines.len());
+    let claims = match parse_claims(&input, args.fabric) {
+        Ok(claims) => claims,
+        Err(errors) => {
+            for (line_number, e) in &errors {
+                eprintln!("{}:{}: {}", args.input, line_number, e);
+            }
+            eprintln!("{} problems found in {}", errors.len(), args.input);
+            std::process::exit(1);
+        }
+    };
 
-    for line in input_lines {
-        claims.push(Claim::new(line));
+    // query and format only need the claims, the rest counts claims per square inch
+    match args.command {
+        Some(Command::Conflicts { top, dot }) => {
+            conflict::report(&claims, &grid(&claims), top, dot)?
+        }
+        Some(Command::Query { at, rect }) => index::query(&claims, at, rect)?,
+        Some(Command::Render { output, scale, bin }) => {
+            render::render(&claims, &grid(&claims), &output, scale, bin)?
+        }
+        Some(Command::Format) => claims.iter().for_each(|claim| println!("{claim}")),
+        None => {
+            let grid = grid(&claims);
+            part_1(&grid)?;
+            part_2(&claims, &grid)?;
+        }
     }
 
+    Ok(())
+}
+
+/// number of claims on every square inch that has any
+fn grid(claims: &[Claim]) -> HashMap<(u32, u32), u32> {
     let mut grid: HashMap<(u32, u32), u32> = HashMap::new();
 
-    for claim in &claims {
+    for claim in claims {
         claim.iter().for_each(|(x, y)| {
             *grid.entry((x, y)).or_default() += 1;
         });
     }
 
-    part_1(&grid)?;
-    part_2(&claims, &grid)?;
-
-    Ok(())
+    grid
 }
 
 fn part_1(grid: &HashMap<(u32, u32), u32>) -> Result<()> {
     let mut repeated_claims = 0;
-    for (_, claim_counts) in grid {
+    for claim_counts in grid.values() {
         if *claim_counts > 1 {
             repeated_claims += 1;
         }
@@ -118,22 +361,20 @@ fn part_1(grid: &HashMap<(u32, u32), u32>) -> Result<()> {
     Ok(())
 }
 
-fn part_2(claims: &Vec<Claim>, grid: &HashMap<(u32, u32), u32>) -> Result<()> {
-    let uncontested: Option<&Claim>;
-
-    uncontested = claims
-        .iter()
-        .filter(|claim| {
-            for (x, y) in claim.iter() {
-                if *grid.get(&(x, y)).unwrap() != 1 {
-                    return false;
-                }
-            }
-            true
-        })
-        .next();
+fn part_2(claims: &[Claim], grid: &HashMap<(u32, u32), u32>) -> Result<()> {
+    let uncontested: Option<&Claim> = claims.iter().find(|claim| is_uncontested(claim, grid));
 
     println!("uncontested: {}", uncontested.unwrap().id);
 
     Ok(())
 }
+
+/// true if no other claim touches any square of this one
+fn is_uncontested(claim: &Claim, grid: &HashMap<(u32, u32), u32>) -> bool {
+    for (x, y) in claim.iter() {
+        if *grid.get(&(x, y)).unwrap() != 1 {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,144 @@
+use gif::{Encoder, Frame};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+
+use crate::Claim;
+
+/// number of shades used for squares claimed twice or more
+const LEVELS: usize = 16;
+/// most pixels drawn before and after scaling, one byte each
+const MAX_PIXELS: u64 = 1 << 28;
+
+// palette indices
+const EMPTY: u8 = 0;
+const SINGLE: u8 = 1;
+const OUTLINE: u8 = 2;
+const CONTESTED: u8 = 3;
+
+/// RGB palette: [empty, single claim, uncontested outline, contested shades...]
+fn palette() -> Vec<u8> {
+    let mut palette = vec![0x1c, 0x1c, 0x1c, 0x5f, 0x87, 0xaf, 0x00, 0xff, 0x5f];
+
+    // contested squares go from yellow to deep red as more claims pile up
+    for level in 0..LEVELS {
+        let t = level as f64 / (LEVELS - 1) as f64;
+        palette.push(0xff - (0x5f as f64 * t) as u8);
+        palette.push(0xd7 - (0xd7 as f64 * t) as u8);
+        palette.push(0x00);
+    }
+
+    palette
+}
+
+/// Draw the fabric with one palette index per pixel. With `bin` > 1 every pixel stands for a
+/// `bin`x`bin` block of squares and shows the most claimed square in it, then every pixel is
+/// blown up to `scale`x`scale`.
+pub fn render(
+    claims: &[Claim],
+    grid: &HashMap<(u32, u32), u32>,
+    output: &str,
+    scale: u32,
+    bin: u32,
+) -> crate::Result<()> {
+    if scale == 0 || bin == 0 {
+        return Err("scale and bin must be at least 1".into());
+    }
+
+    let fabric_width = claims.iter().map(|claim| claim.right()).max().unwrap_or(0);
+    let fabric_height = claims.iter().map(|claim| claim.bottom()).max().unwrap_or(0);
+    let (cols, rows) = (fabric_width.div_ceil(bin), fabric_height.div_ceil(bin));
+    if cols == 0 || rows == 0 {
+        return Err("nothing to draw".into());
+    }
+
+    let (width, height) = (cols as u64 * scale as u64, rows as u64 * scale as u64);
+    if cols as u64 * rows as u64 > MAX_PIXELS
+        || width
+            .checked_mul(height)
+            .is_none_or(|pixels| pixels > MAX_PIXELS)
+    {
+        return Err(format!(
+            "{width}x{height} pixels is too large to draw, try a bigger --bin or smaller --scale"
+        )
+        .into());
+    }
+    let (cols, rows, scale) = (cols as usize, rows as usize, scale as usize);
+    let (width, height) = (width as usize, height as usize);
+    let cell = |x: u32, y: u32| (y / bin) as usize * cols + (x / bin) as usize;
+
+    let mut counts = vec![0u32; cols * rows];
+    for (&(x, y), &count) in grid {
+        let cell = &mut counts[cell(x, y)];
+        *cell = (*cell).max(count);
+    }
+
+    let mut cells = counts
+        .iter()
+        .map(|&count| match count {
+            0 => EMPTY,
+            1 => SINGLE,
+            _ => CONTESTED + (count as usize - 2).min(LEVELS - 1) as u8,
+        })
+        .collect::<Vec<u8>>();
+
+    // outline every claim that does not share a single square
+    for claim in claims
+        .iter()
+        .filter(|claim| crate::is_uncontested(claim, grid))
+    {
+        for (x, y) in claim.iter() {
+            if x == claim.left_pad
+                || x == claim.right() - 1
+                || y == claim.top_pad
+                || y == claim.bottom() - 1
+            {
+                cells[cell(x, y)] = OUTLINE;
+            }
+        }
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in cells.chunks(cols) {
+        let mut line = Vec::with_capacity(width);
+        for &cell in row {
+            line.extend(std::iter::repeat_n(cell, scale));
+        }
+        for _ in 0..scale {
+            pixels.extend_from_slice(&line);
+        }
+    }
+
+    let palette = palette();
+    if output.ends_with(".gif") {
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(
+                format!("{width}x{height} is too large for a gif, try a bigger --bin").into(),
+            );
+        }
+        let mut image = fs::File::create(output)?;
+        let mut encoder = Encoder::new(&mut image, width as u16, height as u16, &palette)?;
+        let frame = Frame {
+            width: width as u16,
+            height: height as u16,
+            buffer: Cow::Borrowed(&pixels),
+            ..Frame::default()
+        };
+        encoder.write_frame(&frame)?;
+    } else if output.ends_with(".ppm") {
+        let mut image = std::io::BufWriter::new(fs::File::create(output)?);
+        write!(image, "P6\n{width} {height}\n255\n")?;
+        for &pixel in &pixels {
+            let idx = pixel as usize * 3;
+            image.write_all(&palette[idx..idx + 3])?;
+        }
+        image.flush()?;
+    } else {
+        return Err(format!("'{output}' should end in .gif or .ppm").into());
+    }
+
+    println!("Successfully written file {output} ({width}x{height})");
+
+    Ok(())
+}
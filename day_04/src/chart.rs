@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use time::Date;
+
+use crate::sleep::{self, Shift, Window};
+
+/// shades for the aggregate heat row, from never asleep to asleep on the most nights
+const SHADES: &[u8] = b" .:-=+*#%@";
+
+/// Print the per night chart followed by one heat row per guard, both limited to the given guard
+/// and to nights between `from` and `to`, inclusive. There is one column per minute of `window`.
+pub fn chart(
+    shifts: &[Shift],
+    window: Window,
+    guard: Option<u32>,
+    from: Option<Date>,
+    to: Option<Date>,
+) -> crate::Result<()> {
+    let shifts = shifts
+        .iter()
+        .filter(|shift| guard.is_none_or(|id| shift.guard == id))
+        .filter(|shift| from.is_none_or(|from| shift.night >= from))
+        .filter(|shift| to.is_none_or(|to| shift.night <= to))
+        .collect::<Vec<&Shift>>();
+
+    let span = window.span();
+    let header = |digit: fn(usize) -> usize| {
+        span.clone()
+            .map(|m| char::from_digit(digit(m) as u32, 10).unwrap())
+            .collect::<String>()
+    };
+
+    println!("Date        ID      Minute");
+    if span.len() > 60 {
+        // hour of the day, only worth printing when more than one hour is shown
+        let hours = span
+            .clone()
+            .map(|m| if m % 60 == 0 { '|' } else { ' ' })
+            .collect::<String>();
+        println!("                    {hours}");
+    }
+    println!("                    {}", header(|m| (m % 60) / 10));
+    println!("                    {}", header(|m| m % 10));
+
+    // same frequency arrays as the puzzle parts, limited to the nights shown
+    let mut guards: BTreeMap<u32, (usize, Vec<u16>)> = BTreeMap::new();
+    for shift in &shifts {
+        let asleep = sleep::asleep_minutes(shift, window);
+        let row = asleep[span.clone()]
+            .iter()
+            .map(|&asleep| if asleep { '#' } else { '.' })
+            .collect::<String>();
+        println!("{}  #{:<6} {}", shift.night, shift.guard, row);
+
+        let (count, freq_count) = guards
+            .entry(shift.guard)
+            .or_insert((0, vec![0; span.len()]));
+        *count += 1;
+        for (freq, &asleep) in freq_count.iter_mut().zip(&asleep[span.clone()]) {
+            if asleep {
+                *freq += 1;
+            }
+        }
+    }
+
+    println!();
+    println!("Guard   Nights  Asleep  Heat");
+    for (id, (count, freq_count)) in &guards {
+        let max = *freq_count.iter().max().unwrap();
+        let heat = freq_count
+            .iter()
+            .map(|&freq| {
+                if freq == 0 {
+                    SHADES[0] as char
+                } else {
+                    // any sleep at all gets at least the faintest visible shade
+                    let level = 1 + (freq as usize * (SHADES.len() - 2)) / max as usize;
+                    SHADES[level.min(SHADES.len() - 1)] as char
+                }
+            })
+            .collect::<String>();
+        let asleep = freq_count.iter().map(|&freq| freq as u32).sum::<u32>();
+        println!("#{:<6} {:<7} {:<7} |{}|", id, count, asleep, heat);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::io::{BufWriter, Write};
+use time::PrimitiveDateTime;
+
+use crate::sleep::Shift;
+
+/// "yyyy-mm-dd hh:mm", for spreadsheets
+fn spreadsheet_time(time: &PrimitiveDateTime) -> String {
+    format!("{} {:02}:{:02}", time.date(), time.hour(), time.minute())
+}
+
+/// "yyyymmddThhmmss", the floating local time of RFC 5545
+fn calendar_time(time: &PrimitiveDateTime) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// One row per nap: guard, night of the shift, start, end and duration in minutes
+pub fn csv(shifts: &[Shift], path: &str) -> crate::Result<usize> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    let mut rows = 0;
+
+    writeln!(file, "guard,date,start,end,duration")?;
+    for shift in shifts {
+        for nap in &shift.naps {
+            writeln!(
+                file,
+                "{},{},{},{},{}",
+                shift.guard,
+                shift.night,
+                spreadsheet_time(&nap.start),
+                spreadsheet_time(&nap.end),
+                (nap.end - nap.start).whole_minutes()
+            )?;
+            rows += 1;
+        }
+    }
+    file.flush()?;
+
+    Ok(rows)
+}
+
+/// One VEVENT per nap. Lines end in CRLF as the format requires; times are floating since the
+/// logs carry no time zone.
+pub fn ics(shifts: &[Shift], path: &str) -> crate::Result<usize> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    let mut events = 0;
+
+    write!(file, "BEGIN:VCALENDAR\r\n")?;
+    write!(file, "VERSION:2.0\r\n")?;
+    write!(file, "PRODID:-//adventOfCode_2018//day_04//EN\r\n")?;
+    for shift in shifts {
+        for nap in &shift.naps {
+            let start = calendar_time(&nap.start);
+            write!(file, "BEGIN:VEVENT\r\n")?;
+            write!(file, "UID:{}-{}@day_04\r\n", shift.guard, start)?;
+            // DTSTAMP has to be UTC; the nap start stands in for it rather than the wall clock so
+            // the same log always exports to the same file
+            write!(file, "DTSTAMP:{}Z\r\n", start)?;
+            write!(file, "DTSTART:{}\r\n", start)?;
+            write!(file, "DTEND:{}\r\n", calendar_time(&nap.end))?;
+            write!(file, "SUMMARY:Guard #{} asleep\r\n", shift.guard)?;
+            write!(file, "END:VEVENT\r\n")?;
+            events += 1;
+        }
+    }
+    write!(file, "END:VCALENDAR\r\n")?;
+    file.flush()?;
+
+    Ok(events)
+}
+
+pub fn export(
+    shifts: &[Shift],
+    csv_path: Option<&str>,
+    ics_path: Option<&str>,
+) -> crate::Result<()> {
+    if csv_path.is_none() && ics_path.is_none() {
+        return Err("export needs --csv and/or --ics".into());
+    }
+
+    if let Some(path) = csv_path {
+        let rows = csv(shifts, path)?;
+        println!("Successfully written {rows} naps to {path}");
+    }
+
+    if let Some(path) = ics_path {
+        let events = ics(shifts, path)?;
+        println!("Successfully written {events} naps to {path}");
+    }
+
+    Ok(())
+}
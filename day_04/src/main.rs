@@ -1,152 +1,201 @@
+use clap::{Parser, Subcommand};
 use regex::Regex;
-use std::{collections::HashMap, fs};
-use time::{Date, Month, PrimitiveDateTime, Time};
+use sleep::Window;
+use std::fs;
+use strategy::Strategy;
+use time::{macros::format_description, Date, Month, PrimitiveDateTime, Time};
+use validate::{validate, Problem, ProblemKind};
+
+mod chart;
+mod export;
+mod sleep;
+mod strategy;
+mod validate;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+#[derive(Parser, Debug)]
+struct Args {
+    /// Name of input file
+    input: String,
+
+    /// Part of the day that counts as sleeping on duty: midnight, shift, day or an hour 0-23
+    #[arg(long, default_value = "midnight", value_parser = sleep::parse_window)]
+    window: Window,
+
+    /// Compare guard selection strategies, one by name or all of them
+    #[arg(long, value_parser = strategy::parse_strategy)]
+    strategy: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the minute by minute chart of every night and a heat row per guard
+    Chart {
+        /// Only show nights of this guard
+        #[arg(long)]
+        guard: Option<u32>,
+
+        /// First night to show, as yyyy-mm-dd
+        #[arg(long, value_parser = parse_date)]
+        from: Option<Date>,
+
+        /// Last night to show, as yyyy-mm-dd
+        #[arg(long, value_parser = parse_date)]
+        to: Option<Date>,
+    },
+
+    /// Print per guard and per minute of the day sleep statistics
+    Stats,
+
+    /// Write every nap to a CSV file and/or an iCalendar file
+    Export {
+        /// CSV file with guard, date, start, end and duration columns
+        #[arg(long)]
+        csv: Option<String>,
+
+        /// iCalendar file with one event per nap
+        #[arg(long)]
+        ics: Option<String>,
+    },
+}
+
+fn parse_date(value: &str) -> std::result::Result<Date, String> {
+    Date::parse(value, format_description!("[year]-[month]-[day]"))
+        .map_err(|e| format!("expected yyyy-mm-dd but got '{value}': {e}"))
+}
+
 fn main() -> Result<()> {
-    let args = std::env::args().collect::<Vec<String>>();
-    if args.len() != 2 {
-        eprintln!("usage: {} <input_filename>", args[0]);
-        std::process::exit(0);
-    }
+    let args = Args::parse();
 
-    let input = fs::read_to_string(&args[1])?;
+    let input = fs::read_to_string(&args.input)?;
     let mut logs: Vec<Log> = vec![];
+    let mut problems: Vec<Problem> = vec![];
     let re_date_time = Regex::new(r"\[(\d+{4})-(\d+{2})-(\d+{2}) (\d+{2}):(\d+{2})\]").unwrap();
-    for line in input.lines() {
-        let (_, [year, month, day, hour, minute]) =
-            re_date_time.captures(line).map(|c| c.extract()).unwrap();
-        let date = Date::from_calendar_date(
-            year.parse().unwrap(),
-            Month::January.nth_next(month.parse::<u8>().unwrap() - 1),
-            day.parse().unwrap(),
-        )?;
-
-        let time = Time::from_hms(hour.parse().unwrap(), minute.parse().unwrap(), 0)?;
-        let log_entry: Log;
-        if line.contains("asleep") {
-            log_entry = Log {
-                time: PrimitiveDateTime::new(date, time),
-                data: LogData::SleepStart(time.minute()),
-            }
-        } else if line.contains("wake") {
-            log_entry = Log {
-                time: PrimitiveDateTime::new(date, time),
-                data: LogData::WakeUp(time.minute()),
-            }
-        } else {
-            let start_idx = line.find('#').unwrap() + 1;
-            let end_idx = line[start_idx..].find(' ').unwrap();
-            log_entry = Log {
-                time: PrimitiveDateTime::new(date, time),
-                data: LogData::Guard(line[start_idx..start_idx + end_idx].parse().unwrap()),
-            }
+    for (idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
         }
 
-        logs.push(log_entry);
+        match parse_log(&re_date_time, idx + 1, line) {
+            Ok(log_entry) => logs.push(log_entry),
+            Err(e) => problems.push(Problem {
+                line: idx + 1,
+                kind: ProblemKind::Unparseable(e.to_string()),
+            }),
+        }
     }
 
+    // stable sort, entries sharing a timestamp stay in input order
     logs.sort_by(|log1, log2| log1.time.partial_cmp(&log2.time).unwrap());
 
-    let mut guards: HashMap<u32, [u16; 60]> = HashMap::new();
-
-    let mut logs_iter = logs.iter();
-
-    let mut current_id = 0;
-    while let Some(log_entry) = logs_iter.next() {
-        match log_entry.data {
-            LogData::Guard(id) => {
-                current_id = id;
-                guards.entry(id).or_insert([0; 60]);
-            }
-            LogData::SleepStart(s) => {
-                let wakeup_minute = logs_iter.next().unwrap().data.inner_num();
-                guards.entry(current_id).and_modify(|freq_count| {
-                    for i in s as u32..wakeup_minute {
-                        freq_count[i as usize] += 1;
-                    }
-                });
-            }
-            LogData::WakeUp(_) => unreachable!(),
-        }
+    let (logs, mut found) = validate(logs);
+    problems.append(&mut found);
+    problems.sort_by_key(|problem| problem.line);
+    for problem in &problems {
+        eprintln!("{}:{}", args.input, problem);
     }
-
-    part_1(&guards)?;
-    part_2(&guards)?;
-
-    Ok(())
-}
-
-fn part_1(guards: &HashMap<u32, [u16; 60]>) -> Result<()> {
-    let mut most_sleepy_id = 0;
-    let mut most_sleepy_duration = 0;
-    for (&guard_id, &freq_count) in guards {
-        let duration = freq_count.iter().sum();
-        if duration >= most_sleepy_duration {
-            most_sleepy_duration = duration;
-            most_sleepy_id = guard_id;
-        }
+    if !problems.is_empty() {
+        eprintln!(
+            "{} problems found in {}, continuing with the repaired log",
+            problems.len(),
+            args.input
+        );
     }
-    let max_min = guards
-        .get(&most_sleepy_id)
-        .unwrap()
-        .iter()
-        .enumerate()
-        .max_by_key(|(_, &x)| x)
-        .map(|(idx, _)| idx)
-        .unwrap();
-    println!("{}", most_sleepy_id * max_min as u32);
 
-    Ok(())
-}
+    let shifts = sleep::shifts(&logs);
+    let guards = strategy::profiles(&shifts, args.window);
 
-fn part_2(guards: &HashMap<u32, [u16; 60]>) -> Result<()> {
-    let mut guard_id = 0;
-    let mut amount = 0;
-    let mut minute = 0;
-
-    for (id, freqs) in guards {
-        let temp_minute = guards
-            .get(id)
-            .unwrap()
-            .iter()
-            .enumerate()
-            .max_by_key(|(_, &x)| x)
-            .map(|(idx, _)| idx)
-            .unwrap();
-
-        if freqs[temp_minute] >= amount {
-            amount = freqs[temp_minute];
-            minute = temp_minute;
-            guard_id = *id;
+    match args.command {
+        Some(Command::Chart { guard, from, to }) => {
+            chart::chart(&shifts, args.window, guard, from, to)?
         }
+        Some(Command::Stats) => sleep::stats(&shifts, args.window)?,
+        Some(Command::Export { csv, ics }) => {
+            export::export(&shifts, csv.as_deref(), ics.as_deref())?
+        }
+        None => match args.strategy {
+            Some(name) => strategy::compare(&guards, &name)?,
+            None => {
+                // the two strategies of the puzzle
+                for choice in [
+                    strategy::MostAsleep.choose(&guards),
+                    strategy::SameMinute.choose(&guards),
+                ] {
+                    match choice {
+                        Some(choice) => println!("{}", choice.answer()),
+                        None => println!("no guard ever slept"),
+                    }
+                }
+            }
+        },
     }
 
-    println!("{}", guard_id * minute as u32);
-
     Ok(())
 }
 
+/// What happened at `Log::time`
 #[derive(Debug)]
 enum LogData {
     Guard(u32),
-    SleepStart(u8),
-    WakeUp(u8),
-}
-
-impl LogData {
-    fn inner_num(&self) -> u32 {
-        match self {
-            LogData::Guard(id) => *id,
-            LogData::SleepStart(s) => *s as u32,
-            LogData::WakeUp(w) => *w as u32,
-        }
-    }
+    SleepStart,
+    WakeUp,
 }
 
 #[derive(Debug)]
 struct Log {
+    /// line number in the input file
+    line: usize,
     time: PrimitiveDateTime,
     data: LogData,
 }
+
+impl Log {
+    /// the night the entry belongs to, shifts starting before midnight count for the next day
+    fn night(&self) -> Date {
+        if self.time.hour() == 23 {
+            self.time.date().next_day().unwrap_or(self.time.date())
+        } else {
+            self.time.date()
+        }
+    }
+}
+
+/// read a single line of the form "[yyyy-mm-dd hh:mm] event"
+fn parse_log(re_date_time: &Regex, line_number: usize, line: &str) -> Result<Log> {
+    let (_, [year, month, day, hour, minute]) = re_date_time
+        .captures(line)
+        .map(|c| c.extract())
+        .ok_or("no [yyyy-mm-dd hh:mm] timestamp")?;
+    let month = month.parse::<u8>()?;
+    if !(1..=12).contains(&month) {
+        return Err(format!("no month number {month}").into());
+    }
+    let date = Date::from_calendar_date(
+        year.parse()?,
+        Month::January.nth_next(month - 1),
+        day.parse()?,
+    )?;
+
+    let time = Time::from_hms(hour.parse()?, minute.parse()?, 0)?;
+    let data = if line.contains("asleep") {
+        LogData::SleepStart
+    } else if line.contains("wake") {
+        LogData::WakeUp
+    } else {
+        let start_idx = line.find('#').ok_or("no guard id, sleep or wake up")? + 1;
+        let end_idx = line[start_idx..]
+            .find(' ')
+            .unwrap_or(line.len() - start_idx);
+        LogData::Guard(line[start_idx..start_idx + end_idx].parse()?)
+    };
+
+    Ok(Log {
+        line: line_number,
+        time: PrimitiveDateTime::new(date, time),
+        data,
+    })
+}
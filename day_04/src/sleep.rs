@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use time::{Date, Duration, PrimitiveDateTime};
+
+use crate::{Log, LogData};
+
+pub const MINUTES_PER_DAY: usize = 24 * 60;
+
+/// how many times a guard was asleep at each minute of the day, 00:00 is index 0
+pub type MinuteCounts = [u16; MINUTES_PER_DAY];
+
+/// One stretch of sleep, `start` inclusive and `end` exclusive
+#[derive(Debug, Clone, Copy)]
+pub struct Nap {
+    pub start: PrimitiveDateTime,
+    pub end: PrimitiveDateTime,
+}
+
+impl Nap {
+    /// every minute the guard spent asleep
+    pub fn minutes(&self) -> impl Iterator<Item = PrimitiveDateTime> {
+        let (start, end) = (self.start, self.end);
+        (0..)
+            .map(move |m| start + Duration::minutes(m))
+            .take_while(move |t| *t < end)
+    }
+}
+
+/// One guard on duty, from their shift entry until the next guard takes over
+#[derive(Debug)]
+pub struct Shift {
+    pub guard: u32,
+    pub night: Date,
+    /// time of the guard's entry
+    pub start: PrimitiveDateTime,
+    /// start of the next shift, `None` for the last one in the log
+    pub end: Option<PrimitiveDateTime>,
+    pub naps: Vec<Nap>,
+}
+
+/// Which part of the day counts towards the statistics
+#[derive(Debug, Clone, Copy)]
+pub enum Window {
+    /// 00:00 to 00:59, what the puzzle looks at
+    Midnight,
+    /// a single hour of the day
+    Hour(u8),
+    /// any time between the start of a shift and the start of the next one, whatever the day
+    Shift,
+    /// every minute of sleep, whatever day it falls on
+    Day,
+}
+
+impl Window {
+    /// minutes of the day this window can cover, for laying out charts
+    pub fn span(&self) -> std::ops::Range<usize> {
+        match self {
+            Window::Midnight => 0..60,
+            Window::Hour(h) => *h as usize * 60..(*h as usize + 1) * 60,
+            Window::Shift | Window::Day => 0..MINUTES_PER_DAY,
+        }
+    }
+
+    pub fn contains(&self, time: &PrimitiveDateTime, shift: &Shift) -> bool {
+        match self {
+            Window::Midnight => time.hour() == 0,
+            Window::Hour(h) => time.hour() == *h,
+            Window::Shift => shift.start <= *time && shift.end.is_none_or(|end| *time < end),
+            Window::Day => true,
+        }
+    }
+}
+
+/// parse "midnight", "shift", "day" or an hour of the day "0".."23"
+pub fn parse_window(value: &str) -> std::result::Result<Window, String> {
+    match value {
+        "midnight" => Ok(Window::Midnight),
+        "shift" => Ok(Window::Shift),
+        "day" => Ok(Window::Day),
+        hour => match hour.parse::<u8>() {
+            Ok(h) if h < 24 => Ok(Window::Hour(h)),
+            _ => Err(format!(
+                "expected midnight, shift, day or an hour 0-23 but got '{value}'"
+            )),
+        },
+    }
+}
+
+pub fn minute_of_day(time: &PrimitiveDateTime) -> usize {
+    time.hour() as usize * 60 + time.minute() as usize
+}
+
+/// Group the validated logs into shifts, every guard entry opens a new one.
+pub fn shifts(logs: &[Log]) -> Vec<Shift> {
+    let mut shifts: Vec<Shift> = Vec::new();
+    let mut logs_iter = logs.iter();
+
+    while let Some(log_entry) = logs_iter.next() {
+        match log_entry.data {
+            LogData::Guard(id) => {
+                if let Some(previous) = shifts.last_mut() {
+                    previous.end = Some(log_entry.time);
+                }
+                shifts.push(Shift {
+                    guard: id,
+                    night: log_entry.night(),
+                    start: log_entry.time,
+                    end: None,
+                    naps: Vec::new(),
+                });
+            }
+            LogData::SleepStart => {
+                // validate() guarantees that the wake up comes right after
+                let wake_up = logs_iter.next().unwrap();
+                if let Some(shift) = shifts.last_mut() {
+                    shift.naps.push(Nap {
+                        start: log_entry.time,
+                        end: wake_up.time,
+                    });
+                }
+            }
+            LogData::WakeUp => unreachable!(),
+        }
+    }
+
+    shifts
+}
+
+/// Count, for every guard, how often they were asleep at each minute of the day. Only the
+/// minutes inside `window` are counted; guards that never slept still get an entry.
+pub fn minute_counts(shifts: &[Shift], window: Window) -> HashMap<u32, MinuteCounts> {
+    let mut guards: HashMap<u32, MinuteCounts> = HashMap::new();
+    for shift in shifts {
+        let freq_count = guards.entry(shift.guard).or_insert([0; MINUTES_PER_DAY]);
+        for nap in &shift.naps {
+            for time in nap.minutes().filter(|time| window.contains(time, shift)) {
+                freq_count[minute_of_day(&time)] += 1;
+            }
+        }
+    }
+    guards
+}
+
+/// Minutes of the day covered by at least one nap of the shift, limited to the window
+pub fn asleep_minutes(shift: &Shift, window: Window) -> [bool; MINUTES_PER_DAY] {
+    let mut asleep = [false; MINUTES_PER_DAY];
+    for nap in &shift.naps {
+        for time in nap.minutes().filter(|time| window.contains(time, shift)) {
+            asleep[minute_of_day(&time)] = true;
+        }
+    }
+    asleep
+}
+
+/// Print, for every guard, how much they slept in the window and the minute of the day they were
+/// most often asleep at, followed by the busiest minutes over all guards.
+pub fn stats(shifts: &[Shift], window: Window) -> crate::Result<()> {
+    let guards = minute_counts(shifts, window);
+    let mut ids = guards.keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+
+    let clock = |minute: usize| format!("{:02}:{:02}", minute / 60, minute % 60);
+
+    println!("Guard   Asleep  Top minute");
+    for id in &ids {
+        let freq_count = &guards[id];
+        let asleep = freq_count.iter().map(|&freq| freq as u32).sum::<u32>();
+        let (minute, &times) = freq_count
+            .iter()
+            .enumerate()
+            .max_by_key(|(minute, &freq)| (freq, std::cmp::Reverse(*minute)))
+            .unwrap();
+        if times == 0 {
+            println!("#{:<6} {:<7} -", id, asleep);
+        } else {
+            println!("#{:<6} {:<7} {} x{}", id, asleep, clock(minute), times);
+        }
+    }
+
+    let mut overall = [0u32; MINUTES_PER_DAY];
+    for freq_count in guards.values() {
+        for (minute, &freq) in freq_count.iter().enumerate() {
+            overall[minute] += freq as u32;
+        }
+    }
+    let mut busiest = overall
+        .iter()
+        .enumerate()
+        .filter(|(_, &total)| total > 0)
+        .collect::<Vec<(usize, &u32)>>();
+    busiest.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(&b.0)));
+
+    println!();
+    println!("Minute  Asleep");
+    for (minute, total) in busiest.iter().take(10) {
+        println!("{}   {}", clock(*minute), total);
+    }
+
+    Ok(())
+}
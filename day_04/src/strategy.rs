@@ -0,0 +1,285 @@
+use crate::sleep::{minute_of_day, MinuteCounts, Nap, Shift, Window, MINUTES_PER_DAY};
+
+/// Everything a strategy may look at for one guard, limited to the observation window
+pub struct GuardSleep {
+    pub id: u32,
+    /// how often the guard was asleep at each minute of the day
+    pub minutes: MinuteCounts,
+    /// minutes asleep on each of the guard's shifts, in log order
+    pub nights: Vec<u32>,
+    /// every nap, cut down to the minutes inside the window
+    pub naps: Vec<Nap>,
+}
+
+impl GuardSleep {
+    pub fn total(&self) -> u32 {
+        self.minutes.iter().map(|&freq| freq as u32).sum()
+    }
+
+    /// minute of the day the guard was most often asleep at
+    pub fn top_minute(&self) -> usize {
+        self.minutes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &x)| x)
+            .map(|(idx, _)| idx)
+            .unwrap()
+    }
+}
+
+/// Gather the sleep of every guard, ordered by guard id.
+pub fn profiles(shifts: &[Shift], window: Window) -> Vec<GuardSleep> {
+    let mut guards: Vec<GuardSleep> = Vec::new();
+
+    for shift in shifts {
+        let idx = match guards.binary_search_by_key(&shift.guard, |guard| guard.id) {
+            Ok(idx) => idx,
+            Err(idx) => {
+                guards.insert(
+                    idx,
+                    GuardSleep {
+                        id: shift.guard,
+                        minutes: [0; MINUTES_PER_DAY],
+                        nights: Vec::new(),
+                        naps: Vec::new(),
+                    },
+                );
+                idx
+            }
+        };
+        let guard = &mut guards[idx];
+
+        let mut asleep = 0;
+        for nap in &shift.naps {
+            let inside = nap
+                .minutes()
+                .filter(|time| window.contains(time, shift))
+                .collect::<Vec<_>>();
+            for time in &inside {
+                guard.minutes[minute_of_day(time)] += 1;
+            }
+            asleep += inside.len() as u32;
+
+            // a nap interrupted by the edge of the window still counts as a single nap
+            if let (Some(first), Some(last)) = (inside.first(), inside.last()) {
+                guard.naps.push(Nap {
+                    start: *first,
+                    end: *last + time::Duration::MINUTE,
+                });
+            }
+        }
+        guard.nights.push(asleep);
+    }
+
+    guards
+}
+
+/// Result of a strategy: the chosen guard, the chosen minute of the day and why
+pub struct Choice {
+    pub guard: u32,
+    pub minute: usize,
+    pub reason: String,
+}
+
+impl Choice {
+    /// the number the puzzle asks for
+    pub fn answer(&self) -> u32 {
+        self.guard * self.minute as u32
+    }
+}
+
+/// A way of picking the guard to sneak past and the minute to do it at
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// `None` if no guard ever slept
+    fn choose(&self, guards: &[GuardSleep]) -> Option<Choice>;
+}
+
+/// Strategy 1: the guard asleep for the most minutes, at their most frequent minute
+pub struct MostAsleep;
+
+impl Strategy for MostAsleep {
+    fn name(&self) -> &'static str {
+        "most-asleep"
+    }
+
+    fn choose(&self, guards: &[GuardSleep]) -> Option<Choice> {
+        let guard = guards
+            .iter()
+            .filter(|guard| guard.total() > 0)
+            .max_by_key(|guard| guard.total())?;
+        Some(Choice {
+            guard: guard.id,
+            minute: guard.top_minute(),
+            reason: format!("{} minutes asleep", guard.total()),
+        })
+    }
+}
+
+/// Strategy 2: the guard most frequently asleep on the same minute
+pub struct SameMinute;
+
+impl Strategy for SameMinute {
+    fn name(&self) -> &'static str {
+        "same-minute"
+    }
+
+    fn choose(&self, guards: &[GuardSleep]) -> Option<Choice> {
+        let guard = guards
+            .iter()
+            .filter(|guard| guard.total() > 0)
+            .max_by_key(|guard| guard.minutes[guard.top_minute()])?;
+        let minute = guard.top_minute();
+        Some(Choice {
+            guard: guard.id,
+            minute,
+            reason: format!("asleep on {} nights at that minute", guard.minutes[minute]),
+        })
+    }
+}
+
+/// The guard whose nightly amount of sleep varies the least, at their most frequent minute.
+/// Ties go to the guard sleeping the most.
+pub struct Consistent;
+
+impl Consistent {
+    fn variance(nights: &[u32]) -> f64 {
+        let mean = nights.iter().sum::<u32>() as f64 / nights.len() as f64;
+        nights
+            .iter()
+            .map(|&night| (night as f64 - mean).powi(2))
+            .sum::<f64>()
+            / nights.len() as f64
+    }
+}
+
+impl Strategy for Consistent {
+    fn name(&self) -> &'static str {
+        "consistent"
+    }
+
+    fn choose(&self, guards: &[GuardSleep]) -> Option<Choice> {
+        let guard = guards
+            .iter()
+            .filter(|guard| guard.total() > 0)
+            .min_by(|a, b| {
+                Consistent::variance(&a.nights)
+                    .total_cmp(&Consistent::variance(&b.nights))
+                    .then(b.total().cmp(&a.total()))
+            })?;
+        Some(Choice {
+            guard: guard.id,
+            minute: guard.top_minute(),
+            reason: format!(
+                "variance {:.1} over {} nights",
+                Consistent::variance(&guard.nights),
+                guard.nights.len()
+            ),
+        })
+    }
+}
+
+/// The guard who took the longest single nap, at the minute that nap started
+pub struct LongestNap;
+
+impl Strategy for LongestNap {
+    fn name(&self) -> &'static str {
+        "longest-nap"
+    }
+
+    fn choose(&self, guards: &[GuardSleep]) -> Option<Choice> {
+        let (guard, nap) = guards
+            .iter()
+            .flat_map(|guard| guard.naps.iter().map(move |nap| (guard, nap)))
+            .max_by_key(|(_, nap)| nap.end - nap.start)?;
+        Some(Choice {
+            guard: guard.id,
+            minute: minute_of_day(&nap.start),
+            reason: format!(
+                "{} minute nap on {}",
+                (nap.end - nap.start).whole_minutes(),
+                nap.start.date()
+            ),
+        })
+    }
+}
+
+/// The guard who slept on the most nights, at their most frequent minute. Ties go to the guard
+/// sleeping the most.
+pub struct MostNights;
+
+impl Strategy for MostNights {
+    fn name(&self) -> &'static str {
+        "most-nights"
+    }
+
+    fn choose(&self, guards: &[GuardSleep]) -> Option<Choice> {
+        let nights_asleep =
+            |guard: &GuardSleep| guard.nights.iter().filter(|&&night| night > 0).count();
+        let guard = guards
+            .iter()
+            .filter(|guard| guard.total() > 0)
+            .max_by_key(|guard| (nights_asleep(guard), guard.total()))?;
+        Some(Choice {
+            guard: guard.id,
+            minute: guard.top_minute(),
+            reason: format!(
+                "asleep on {} of {} nights",
+                nights_asleep(guard),
+                guard.nights.len()
+            ),
+        })
+    }
+}
+
+pub fn all() -> Vec<Box<dyn Strategy>> {
+    vec![
+        Box::new(MostAsleep),
+        Box::new(SameMinute),
+        Box::new(Consistent),
+        Box::new(LongestNap),
+        Box::new(MostNights),
+    ]
+}
+
+/// parse a strategy name, or "all"
+pub fn parse_strategy(value: &str) -> std::result::Result<String, String> {
+    if value == "all" || all().iter().any(|strategy| strategy.name() == value) {
+        Ok(value.to_string())
+    } else {
+        let names = all()
+            .iter()
+            .map(|strategy| strategy.name())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        Err(format!("expected all or one of {names} but got '{value}'"))
+    }
+}
+
+/// Run the named strategy, or every strategy with "all", and print a comparison table
+pub fn compare(guards: &[GuardSleep], name: &str) -> crate::Result<()> {
+    println!(
+        "{:<13} {:<7} {:<7} {:<9} Reason",
+        "Strategy", "Guard", "Minute", "Answer"
+    );
+    for strategy in all()
+        .iter()
+        .filter(|strategy| name == "all" || strategy.name() == name)
+    {
+        match strategy.choose(guards) {
+            Some(choice) => println!(
+                "{:<13} #{:<6} {:02}:{:02}   {:<9} {}",
+                strategy.name(),
+                choice.guard,
+                choice.minute / 60,
+                choice.minute % 60,
+                choice.answer(),
+                choice.reason
+            ),
+            None => println!("{:<13} no guard ever slept", strategy.name()),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+use time::{Duration, PrimitiveDateTime, Time};
+
+use crate::{Log, LogData};
+
+/// Something wrong with the guard log, tied to the line of the input it was found on
+#[derive(Debug)]
+pub struct Problem {
+    pub line: usize,
+    pub kind: ProblemKind,
+}
+
+#[derive(Debug)]
+pub enum ProblemKind {
+    /// the line could not be read at all; it is skipped
+    Unparseable(String),
+    /// same timestamp as an earlier line; the later line is dropped
+    DuplicateTimestamp { first_line: usize },
+    /// sleep or wake before any guard began a shift; the entry is dropped
+    BeforeDuty,
+    /// wake up without a matching fall asleep; the entry is dropped
+    OrphanedWake,
+    /// fall asleep never followed by a wake up in the same shift; the sleep is closed by the next
+    /// entry or at the end of the hour it started in, whichever comes first, at `until`
+    OrphanedSleep { until: PrimitiveDateTime },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.line)?;
+        match &self.kind {
+            ProblemKind::Unparseable(reason) => write!(f, "skipped, {reason}"),
+            ProblemKind::DuplicateTimestamp { first_line } => {
+                write!(f, "dropped, same timestamp as line {first_line}")
+            }
+            ProblemKind::BeforeDuty => write!(f, "dropped, no guard is on duty yet"),
+            ProblemKind::OrphanedWake => write!(f, "dropped, wakes up without falling asleep"),
+            ProblemKind::OrphanedSleep { until } => {
+                write!(
+                    f,
+                    "falls asleep but never wakes up, sleep ends at {} {:02}:{:02}, the next entry \
+                     or the end of the hour, whichever comes first",
+                    until.date(),
+                    until.hour(),
+                    until.minute()
+                )
+            }
+        }
+    }
+}
+
+/// Recovery policy for the orphaned sleep: the guard is assumed asleep until the next entry of
+/// the log, but never past the end of the hour the nap started in. The same for every
+/// observation window, so the repaired log does not depend on what is being looked at.
+fn closing_wake(sleep: &Log, next: Option<PrimitiveDateTime>) -> Log {
+    let hour = PrimitiveDateTime::new(
+        sleep.time.date(),
+        Time::from_hms(sleep.time.hour(), 0, 0).unwrap(),
+    );
+    let end_of_hour = hour + Duration::hours(1);
+    Log {
+        line: sleep.line,
+        time: next.map_or(end_of_hour, |next| next.min(end_of_hour)),
+        data: LogData::WakeUp,
+    }
+}
+
+/// Walk the sorted logs and repair them so that every `SleepStart` is immediately followed by
+/// its `WakeUp` and every sleep happens while a guard is on duty. The repaired logs are returned
+/// together with every problem found, in input order. A guard entry always ends the shift before
+/// it, handovers included, so two guards are never on duty at once.
+pub fn validate(logs: Vec<Log>) -> (Vec<Log>, Vec<Problem>) {
+    let mut problems = Vec::new();
+    let mut cleaned: Vec<Log> = Vec::with_capacity(logs.len());
+
+    let mut last_time: Option<(PrimitiveDateTime, usize)> = None;
+    let mut on_duty = false;
+    let mut asleep: Option<usize> = None; // index in `cleaned` of the open sleep
+
+    for log in logs {
+        if let Some((time, first_line)) = last_time {
+            if time == log.time {
+                problems.push(Problem {
+                    line: log.line,
+                    kind: ProblemKind::DuplicateTimestamp { first_line },
+                });
+                continue;
+            }
+        }
+        last_time = Some((log.time, log.line));
+
+        match log.data {
+            LogData::Guard(_) => {
+                if let Some(idx) = asleep.take() {
+                    let wake = closing_wake(&cleaned[idx], Some(log.time));
+                    problems.push(Problem {
+                        line: cleaned[idx].line,
+                        kind: ProblemKind::OrphanedSleep { until: wake.time },
+                    });
+                    cleaned.push(wake);
+                }
+
+                on_duty = true;
+                cleaned.push(log);
+            }
+            LogData::SleepStart => {
+                if !on_duty {
+                    problems.push(Problem {
+                        line: log.line,
+                        kind: ProblemKind::BeforeDuty,
+                    });
+                    continue;
+                }
+
+                if let Some(idx) = asleep.take() {
+                    let wake = closing_wake(&cleaned[idx], Some(log.time));
+                    problems.push(Problem {
+                        line: cleaned[idx].line,
+                        kind: ProblemKind::OrphanedSleep { until: wake.time },
+                    });
+                    cleaned.push(wake);
+                }
+
+                asleep = Some(cleaned.len());
+                cleaned.push(log);
+            }
+            LogData::WakeUp => {
+                if !on_duty {
+                    problems.push(Problem {
+                        line: log.line,
+                        kind: ProblemKind::BeforeDuty,
+                    });
+                } else if asleep.take().is_none() {
+                    problems.push(Problem {
+                        line: log.line,
+                        kind: ProblemKind::OrphanedWake,
+                    });
+                } else {
+                    cleaned.push(log);
+                }
+            }
+        }
+    }
+
+    if let Some(idx) = asleep {
+        let wake = closing_wake(&cleaned[idx], None);
+        problems.push(Problem {
+            line: cleaned[idx].line,
+            kind: ProblemKind::OrphanedSleep { until: wake.time },
+        });
+        cleaned.push(wake);
+    }
+
+    problems.sort_by_key(|problem| problem.line);
+    (cleaned, problems)
+}
@@ -1,123 +1,270 @@
+use clap::Parser;
+use rules::RuleSet;
 use std::fs;
-use std::str;
+use std::io::{ErrorKind, Read};
+use trace::{Reaction, Trace};
+
+mod rules;
+mod trace;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+#[derive(Parser, Debug)]
+struct Args {
+    /// Name of input file
+    input: String,
+
+    /// File with the reaction rules, the puzzle's case polarity when left out
+    #[arg(long)]
+    rules: Option<String>,
+
+    /// Write every reaction of part 1 to this file
+    #[arg(long)]
+    trace: Option<String>,
+
+    /// Replay the reactions of part 1 in the terminal
+    #[arg(long)]
+    animate: bool,
+
+    /// Milliseconds between two frames of the animation
+    #[arg(long, default_value_t = 100)]
+    delay: u64,
+
+    /// Print the length left after removing each unit type, not just the shortest
+    #[arg(long)]
+    report: bool,
+
+    /// Number of unit types removed together in part 2
+    #[arg(long, default_value_t = 1)]
+    sets: usize,
+}
+
+/// number of bytes read from the polymer at a time
+const CHUNK: usize = 64 * 1024;
+
 fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("usage: {} <input_file>", args[0]);
-        std::process::exit(1);
-    }
+    let args = Args::parse();
 
-    let input = fs::read_to_string(&args[1])?;
+    let rules = match &args.rules {
+        Some(path) => RuleSet::from_file(path)?,
+        None => RuleSet::polarity(),
+    };
 
-    part1(&input)?;
-    part2(&input)?;
+    let reduced = part1(&args, &rules)?;
+    part2(&args, &rules, &reduced)?;
 
     Ok(())
 }
 
-fn part1(input: &str) -> Result<()> {
-    let stack = Stack::try_from(input.trim())?;
-    println!("{}", stack.next);
+fn part1(args: &Args, rules: &RuleSet) -> Result<Stack> {
+    if args.trace.is_none() && !args.animate {
+        let stack = Stack::new().reduce(fs::File::open(&args.input)?, rules, |_| false)?;
+        println!("{}", stack.len());
+        return Ok(stack);
+    }
 
-    Ok(())
+    let stack = Stack::traced().reduce(fs::File::open(&args.input)?, rules, |_| false)?;
+    let trace = stack.trace.as_ref().unwrap();
+
+    if args.animate {
+        // the replay needs the whole polymer, only sensible for small ones
+        let polymer = fs::read(&args.input)?;
+        trace.animate(&polymer, std::time::Duration::from_millis(args.delay))?;
+    }
+
+    if let Some(path) = &args.trace {
+        trace.write_log(path)?;
+        println!(
+            "Successfully written {} reactions to {}",
+            trace.reactions.len(),
+            path
+        );
+    }
+
+    println!("{}", stack.len());
+
+    Ok(stack)
 }
 
-fn part2(input: &str) -> Result<()> {
-    let mut lengths: Vec<usize> = Vec::with_capacity(26);
-    for c in 'a'..='z' {
-        let stack = Stack::try_from_but_c(input.trim(), c)?;
-        lengths.push(stack.next);
+fn part2(args: &Args, rules: &RuleSet, reduced: &Stack) -> Result<()> {
+    let types = (0..=255u8)
+        .filter(|&unit_type| reduced.types[unit_type as usize])
+        .collect::<Vec<u8>>();
+    if types.is_empty() && args.sets > 0 {
+        // nothing left to remove, the polymer stays as short as part 1 made it
+        println!("{}", reduced.len());
+        return Ok(());
+    }
+    if args.sets == 0 || args.sets > types.len() {
+        return Err(format!(
+            "cannot remove sets of {} out of {} unit types",
+            args.sets,
+            types.len()
+        )
+        .into());
     }
 
-    let min = lengths
-        .iter()
-        .enumerate()
-        .min_by_key(|(_, &len)| len)
-        .map(|(_, len)| len)
-        .unwrap();
+    let mut lengths: Vec<(Vec<u8>, usize)> = Vec::new();
+    for removed in combinations(&types, args.sets) {
+        let skip = |unit: u8| removed.contains(&unit_type(unit));
+        // Removing units and reacting can be done in either order as long as reactions only
+        // annihilate opposite pairs, so the already reduced polymer is a much shorter start.
+        let stack = if rules.commutes_with_removal() {
+            Stack::new().reduce(reduced.slots.as_slice(), rules, skip)?
+        } else {
+            Stack::new().reduce(fs::File::open(&args.input)?, rules, skip)?
+        };
+        lengths.push((removed, stack.len()));
+    }
+
+    if args.report {
+        println!("removed  length");
+        for (removed, len) in &lengths {
+            let name = removed
+                .iter()
+                .map(|&unit| unit.escape_ascii().to_string())
+                .collect::<Vec<String>>()
+                .join("+");
+            println!("{:<8} {}", name, len);
+        }
+    }
+
+    let min = lengths.iter().map(|(_, len)| len).min().unwrap();
 
     println!("{}", min);
     Ok(())
 }
 
-const MAXDEPTH: usize = 50000;
+/// units that are removed together, ASCII letters of both polarities are the same type
+fn unit_type(unit: u8) -> u8 {
+    unit.to_ascii_lowercase()
+}
+
+/// every way of picking `k` items out of `items`, in order
+fn combinations(items: &[u8], k: usize) -> Vec<Vec<u8>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+
+    let mut picks = Vec::new();
+    for (idx, &item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[idx + 1..], k - 1) {
+            rest.insert(0, item);
+            picks.push(rest);
+        }
+    }
+    picks
+}
+
+/// The reduced polymer. Only units that survived so far are kept, so memory grows with the
+/// result and not with the input.
 struct Stack {
-    slots: [u8; MAXDEPTH],
-    next: usize,
+    slots: Vec<u8>,
+    /// unit types that were read, whether they survived or not
+    types: [bool; 256],
+    /// only kept when the reactions are being traced
+    trace: Option<Trace>,
 }
 
 impl Stack {
-    fn push(&mut self, x: u8) -> Result<()> {
-        if self.next >= MAXDEPTH {
-            return Err("stack overflow".into());
-        } else {
-            self.slots[self.next] = x;
-            self.next += 1;
-            Ok(())
+    fn new() -> Self {
+        Stack {
+            slots: Vec::new(),
+            types: [false; 256],
+            trace: None,
         }
     }
 
-    fn pop(&mut self) -> Option<u8> {
-        if self.next != 0 {
-            self.next -= 1;
-            Some(self.slots[self.next])
-        } else {
-            None
+    fn traced() -> Self {
+        Stack {
+            slots: Vec::new(),
+            types: [false; 256],
+            trace: Some(Trace::default()),
         }
     }
 
-    fn polarity(&self) -> bool {
-        if self.next > 1 {
-            return self.slots[self.next - 1].abs_diff(self.slots[self.next - 2]) == 32;
-        }
+    fn len(&self) -> usize {
+        self.slots.len()
+    }
 
-        false
+    fn push(&mut self, x: u8, origin: u64) {
+        self.slots.push(x);
+        if let Some(trace) = &mut self.trace {
+            trace.origins.push(origin);
+        }
     }
 
-    fn try_from_but_c(
-        value: &str,
-        c: char,
-    ) -> std::result::Result<Self, Box<dyn std::error::Error>> {
-        let mut stack = Stack {
-            slots: [0; MAXDEPTH],
-            next: 0,
+    /// Add a unit at the end of the polymer and let it react with whatever is left before it.
+    /// Whatever a reaction produces is added back the same way, so it can react in turn.
+    fn react(&mut self, x: u8, origin: u64, rules: &RuleSet) {
+        self.push(x, origin);
+        let Some(rule) = rules.matching(&self.slots) else {
+            return;
         };
 
-        for ch in value.bytes().filter(|&ch| {
-            let diff = (c as u8).abs_diff(ch);
-            diff != 0 && diff != 32
-        }) {
-            stack.push(ch)?;
-            while let true = stack.polarity() {
-                let _ = stack.pop();
-                let _ = stack.pop();
+        // products are rare, only allocate when something has to be pushed back
+        let mut pending = Vec::new();
+        let mut rule = Some(rule);
+        loop {
+            if let Some(rule) = rule {
+                let depth = self.slots.len();
+                let start = depth - rule.pattern.len();
+                self.slots.truncate(start);
+
+                // produced units take the place of the first units of the pattern
+                match &mut self.trace {
+                    Some(trace) => {
+                        let origins = trace.origins.split_off(start);
+                        pending.extend(rule.product.iter().copied().zip(origins.clone()).rev());
+                        trace.reactions.push(Reaction {
+                            step: trace.reactions.len() + 1,
+                            depth,
+                            positions: origins,
+                            pattern: rule.pattern.clone(),
+                            product: rule.product.clone(),
+                        });
+                    }
+                    None => pending.extend(rule.product.iter().rev().map(|&unit| (unit, origin))),
+                }
             }
-        }
 
-        Ok(stack)
+            let Some((unit, origin)) = pending.pop() else {
+                break;
+            };
+            self.push(unit, origin);
+            rule = rules.matching(&self.slots);
+        }
     }
-}
 
-impl TryFrom<&str> for Stack {
-    type Error = Box<dyn std::error::Error>;
+    /// Reduce a polymer read in chunks from `reader`, leaving out whitespace and every unit for
+    /// which `skip` is true.
+    fn reduce(
+        mut self,
+        mut reader: impl Read,
+        rules: &RuleSet,
+        skip: impl Fn(u8) -> bool,
+    ) -> Result<Self> {
+        let mut buffer = vec![0u8; CHUNK];
+        let mut offset = 0u64;
 
-    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
-        let mut stack = Stack {
-            slots: [0; MAXDEPTH],
-            next: 0,
-        };
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Box::new(e)),
+            };
 
-        for c in value.bytes() {
-            stack.push(c)?;
-            while let true = stack.polarity() {
-                let _ = stack.pop();
-                let _ = stack.pop();
+            for (idx, &unit) in buffer[..read].iter().enumerate() {
+                if unit.is_ascii_whitespace() || skip(unit) {
+                    continue;
+                }
+                self.types[unit_type(unit) as usize] = true;
+                self.react(unit, offset + idx as u64, rules);
             }
+            offset += read as u64;
         }
 
-        Ok(stack)
+        Ok(self)
     }
 }
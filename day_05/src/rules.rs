@@ -0,0 +1,133 @@
+use std::fs;
+
+/// `pattern` at the end of the polymer is replaced with `product`, an empty product means the
+/// units annihilate
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub pattern: Vec<u8>,
+    pub product: Vec<u8>,
+}
+
+/// Every reaction the reducer knows about, indexed by the last unit of their pattern so that only
+/// the rules that can possibly fire are tried after a push.
+#[derive(Debug)]
+pub struct RuleSet {
+    by_last: Vec<Vec<Rule>>,
+}
+
+impl RuleSet {
+    fn new() -> Self {
+        RuleSet {
+            by_last: vec![Vec::new(); 256],
+        }
+    }
+
+    /// The puzzle's chemistry: a unit and the same unit of opposite polarity (case) annihilate.
+    pub fn polarity() -> Self {
+        let mut rules = RuleSet::new();
+        rules.add_polarity();
+        rules
+    }
+
+    fn add_polarity(&mut self) {
+        for lower in b'a'..=b'z' {
+            let upper = lower.to_ascii_uppercase();
+            self.add(Rule {
+                pattern: vec![lower, upper],
+                product: vec![],
+            });
+            self.add(Rule {
+                pattern: vec![upper, lower],
+                product: vec![],
+            });
+        }
+    }
+
+    fn add(&mut self, rule: Rule) {
+        let last = *rule.pattern.last().unwrap() as usize;
+        self.by_last[last].push(rule);
+        // longest pattern wins when several match
+        self.by_last[last].sort_by_key(|rule| std::cmp::Reverse(rule.pattern.len()));
+    }
+
+    /// True if every rule annihilates a unit with the same unit of opposite polarity, like the
+    /// puzzle's. Such rules always reduce a polymer to the same result whatever order they fire
+    /// in, and every reaction takes out units of one type only, so removing a unit type before or
+    /// after the reduction leaves the same polymer. Rules of one type that are longer can reduce
+    /// differently depending on what a removal brings together: with `aA ->` and `aaA ->`,
+    /// `abaA` reduces to `ab` and then `a` without the `b`, but `aaA` reduces to nothing.
+    pub fn commutes_with_removal(&self) -> bool {
+        self.by_last.iter().flatten().all(|rule| {
+            let [first, second] = rule.pattern[..] else {
+                return false;
+            };
+            rule.product.is_empty()
+                && first != second
+                && crate::unit_type(first) == crate::unit_type(second)
+        })
+    }
+
+    /// the rule whose pattern the polymer currently ends with, if any
+    pub fn matching(&self, polymer: &[u8]) -> Option<&Rule> {
+        let last = *polymer.last()? as usize;
+        self.by_last[last]
+            .iter()
+            .find(|rule| polymer.ends_with(&rule.pattern))
+    }
+
+    /// Read rules from a file, one per line:
+    ///
+    /// ```text
+    /// # comment
+    /// case        every unit annihilates with its opposite case, like the puzzle
+    /// xy ->       x followed by y annihilate
+    /// abc -> d    a, b and c react into d
+    /// ```
+    ///
+    /// A product has to be shorter than its pattern so that the reduction always ends.
+    pub fn from_file(path: &str) -> crate::Result<Self> {
+        let mut rules = RuleSet::new();
+
+        for (idx, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line == "case" {
+                rules.add_polarity();
+                continue;
+            }
+
+            let (pattern, product) = line
+                .split_once("->")
+                .ok_or_else(|| format!("{path}:{}: expected 'pattern -> product'", idx + 1))?;
+            let (pattern, product) = (pattern.trim().as_bytes(), product.trim().as_bytes());
+
+            if pattern.is_empty() {
+                return Err(format!("{path}:{}: empty pattern", idx + 1).into());
+            }
+            if product.len() >= pattern.len() {
+                return Err(format!(
+                    "{path}:{}: product must be shorter than the pattern",
+                    idx + 1
+                )
+                .into());
+            }
+            if pattern
+                .iter()
+                .chain(product)
+                .any(|unit| unit.is_ascii_whitespace())
+            {
+                return Err(format!("{path}:{}: units cannot be whitespace", idx + 1).into());
+            }
+
+            rules.add(Rule {
+                pattern: pattern.to_vec(),
+                product: product.to_vec(),
+            });
+        }
+
+        Ok(rules)
+    }
+}
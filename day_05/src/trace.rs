@@ -0,0 +1,103 @@
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::thread;
+use std::time::Duration;
+
+/// One reaction as it happened during the reduction
+#[derive(Debug)]
+pub struct Reaction {
+    /// 1 for the first reaction, 2 for the second...
+    pub step: usize,
+    /// stack depth right before the reacting units were taken off
+    pub depth: usize,
+    /// byte offsets in the input of the units that reacted, in polymer order. A produced unit
+    /// takes over the offset of the unit it replaces.
+    pub positions: Vec<u64>,
+    pub pattern: Vec<u8>,
+    pub product: Vec<u8>,
+}
+
+/// What a traced reduction remembers: where every surviving unit came from and every reaction
+#[derive(Debug, Default)]
+pub struct Trace {
+    pub origins: Vec<u64>,
+    pub reactions: Vec<Reaction>,
+}
+
+impl Trace {
+    /// One tab separated line per reaction
+    pub fn write_log(&self, path: &str) -> crate::Result<()> {
+        let mut file = BufWriter::new(fs::File::create(path)?);
+
+        writeln!(file, "step\tdepth\tpositions\tpattern\tproduct")?;
+        for reaction in &self.reactions {
+            let positions = reaction
+                .positions
+                .iter()
+                .map(|position| position.to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{}\t{}",
+                reaction.step,
+                reaction.depth,
+                positions,
+                String::from_utf8_lossy(&reaction.pattern),
+                String::from_utf8_lossy(&reaction.product)
+            )?;
+        }
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Replay the reactions on the original polymer, redrawing it in the terminal after each one.
+    /// Units about to react are shown in brackets.
+    pub fn animate(&self, polymer: &[u8], delay: Duration) -> crate::Result<()> {
+        let mut units = polymer
+            .iter()
+            .map(|&unit| (!unit.is_ascii_whitespace()).then_some(unit))
+            .collect::<Vec<Option<u8>>>();
+
+        let draw = |units: &[Option<u8>], marked: &[u64], caption: &str| {
+            let mut frame = String::from("\x1b[2J\x1b[H");
+            for (position, unit) in units.iter().enumerate() {
+                if let Some(unit) = unit {
+                    if marked.contains(&(position as u64)) {
+                        frame.push_str(&format!("[{}]", *unit as char));
+                    } else {
+                        frame.push(*unit as char);
+                    }
+                }
+            }
+            frame.push_str(&format!("\n\n{caption}\n"));
+            print!("{frame}");
+            let _ = std::io::stdout().flush();
+        };
+
+        draw(&units, &[], "start");
+        thread::sleep(delay);
+
+        for reaction in &self.reactions {
+            let caption = format!(
+                "step {}: {} -> {} at depth {}",
+                reaction.step,
+                String::from_utf8_lossy(&reaction.pattern),
+                String::from_utf8_lossy(&reaction.product),
+                reaction.depth
+            );
+            draw(&units, &reaction.positions, &caption);
+            thread::sleep(delay);
+
+            for (idx, &position) in reaction.positions.iter().enumerate() {
+                units[position as usize] = reaction.product.get(idx).copied();
+            }
+        }
+
+        let left = units.iter().flatten().count();
+        draw(&units, &[], &format!("done, {left} units left"));
+
+        Ok(())
+    }
+}
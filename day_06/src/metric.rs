@@ -0,0 +1,402 @@
+use crate::volume::Bounds;
+use crate::voronoi::Voronoi;
+
+/// A way of measuring the distance between two locations
+pub trait Metric {
+    /// The number of axes locations need, `None` if any number works
+    fn axes(&self) -> Option<usize> {
+        Some(2)
+    }
+
+    /// Exact number that orders distances, used to decide which coordinate is closest and
+    /// whether two are equally close. Not necessarily the distance itself.
+    fn rank(&self, a: &[i32], b: &[i32]) -> u64;
+
+    /// the distance itself, used when distances are added up
+    fn distance(&self, a: &[i32], b: &[i32]) -> f64;
+
+    /// Grid steps over `dims` axes and their costs, if the distance is always the cost of the
+    /// cheapest path made of such steps. Lets the grid be labelled with a flood fill instead of
+    /// comparing every coordinate for every cell.
+    fn steps(&self, _dims: usize) -> Option<Vec<(Vec<i32>, u64)>> {
+        None
+    }
+
+    /// The weight of each of the `dims` axes if the distance is the sum of every weight times
+    /// the difference along its axis, so that sums of distances can be worked out one axis at
+    /// a time
+    fn axis_weights(&self, _dims: usize) -> Option<Vec<u64>> {
+        None
+    }
+
+    /// Box holding every finite area and every cell `infinite` looks at. Finite areas are not
+    /// always inside the bounding box `bounds` of the coordinates.
+    fn extent(&self, coordinates: &[Vec<i32>], bounds: &Bounds) -> Bounds;
+
+    /// For every coordinate, whether it is closest to infinitely many locations. `voronoi`
+    /// covers `extent()`.
+    fn infinite(&self, coordinates: &[Vec<i32>], voronoi: &Voronoi, bounds: &Bounds) -> Vec<bool>;
+}
+
+/// `|dx| * wx + |dy| * wy + ...` with one weight per axis, plain Manhattan over any number of
+/// axes when there are no weights
+pub struct Manhattan {
+    pub weights: Vec<u32>,
+}
+
+impl Manhattan {
+    fn weight(&self, axis: usize) -> u64 {
+        self.weights.get(axis).map_or(1, |&weight| weight as u64)
+    }
+}
+
+impl Metric for Manhattan {
+    fn axes(&self) -> Option<usize> {
+        match self.weights.len() {
+            0 => None,
+            axes => Some(axes),
+        }
+    }
+
+    fn rank(&self, a: &[i32], b: &[i32]) -> u64 {
+        a.iter()
+            .zip(b)
+            .enumerate()
+            .map(|(axis, (a, b))| a.abs_diff(*b) as u64 * self.weight(axis))
+            .sum()
+    }
+
+    fn distance(&self, a: &[i32], b: &[i32]) -> f64 {
+        self.rank(a, b) as f64
+    }
+
+    fn steps(&self, dims: usize) -> Option<Vec<(Vec<i32>, u64)>> {
+        let mut steps = Vec::with_capacity(2 * dims);
+        for axis in 0..dims {
+            for by in [-1, 1] {
+                let mut step = vec![0; dims];
+                step[axis] = by;
+                steps.push((step, self.weight(axis)));
+            }
+        }
+        Some(steps)
+    }
+
+    fn axis_weights(&self, dims: usize) -> Option<Vec<u64>> {
+        Some((0..dims).map(|axis| self.weight(axis)).collect())
+    }
+
+    /// Whoever owns a cell outside the bounding box has an infinite area, see `infinite`
+    fn extent(&self, _coordinates: &[Vec<i32>], bounds: &Bounds) -> Bounds {
+        bounds.grown(0..bounds.dims(), 1)
+    }
+
+    /// Outside the bounding box, stepping away from it adds the same amount to the distance of
+    /// every coordinate, so whoever owns a cell just outside the box owns every cell behind it.
+    /// Any cell owned far away can be walked back to that shell the same way.
+    fn infinite(&self, coordinates: &[Vec<i32>], voronoi: &Voronoi, _bounds: &Bounds) -> Vec<bool> {
+        let mut infinite = vec![false; coordinates.len()];
+        let extent = &voronoi.bounds;
+        for cell in 0..extent.cells() {
+            if let Some(owner) = voronoi.owner_of(cell) {
+                if extent.on_shell(&extent.point(cell)) {
+                    infinite[owner] = true;
+                }
+            }
+        }
+        infinite
+    }
+}
+
+/// `max(|dx|, |dy|)`, moving like a chess king
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+    fn rank(&self, a: &[i32], b: &[i32]) -> u64 {
+        a[0].abs_diff(b[0]).max(a[1].abs_diff(b[1])) as u64
+    }
+
+    fn distance(&self, a: &[i32], b: &[i32]) -> f64 {
+        self.rank(a, b) as f64
+    }
+
+    fn steps(&self, _dims: usize) -> Option<Vec<(Vec<i32>, u64)>> {
+        let mut steps = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if (dx, dy) != (0, 0) {
+                    steps.push((vec![dx, dy], 1));
+                }
+            }
+        }
+        Some(steps)
+    }
+
+    /// The ring around the bounding box in u and v, see `infinite`, which can stick out well
+    /// past the usual bounding box
+    fn extent(&self, coordinates: &[Vec<i32>], bounds: &Bounds) -> Bounds {
+        let (u_min, u_max, v_min, v_max) = rotated_edges(coordinates);
+        Bounds {
+            min: vec![
+                bounds.min[0].min((u_min + v_min) / 2 - 3),
+                bounds.min[1].min((u_min - v_max) / 2 - 3),
+            ],
+            max: vec![
+                bounds.max[0].max((u_max + v_max) / 2 + 3),
+                bounds.max[1].max((u_max - v_min) / 2 + 3),
+            ],
+        }
+    }
+
+    /// Chebyshev distance is half the Manhattan distance in the rotated coordinates u = x + y,
+    /// v = x - y, so the ring test of Manhattan applies to the bounding box in u and v. Only
+    /// cells with u and v of equal parity exist, hence a ring two wide.
+    fn infinite(&self, coordinates: &[Vec<i32>], voronoi: &Voronoi, _bounds: &Bounds) -> Vec<bool> {
+        let (u_min, u_max, v_min, v_max) = rotated_edges(coordinates);
+
+        let mut infinite = vec![false; coordinates.len()];
+        let mut mark = |u: i32, v: i32| {
+            if (u + v) % 2 != 0 {
+                return;
+            }
+            if let Some(owner) = voronoi.owner(&[(u + v) / 2, (u - v) / 2]) {
+                infinite[owner] = true;
+            }
+        };
+
+        for v in (v_min - 2)..=(v_max + 2) {
+            for u in [u_min - 2, u_min - 1, u_max + 1, u_max + 2] {
+                mark(u, v);
+            }
+        }
+        for u in (u_min - 2)..=(u_max + 2) {
+            for v in [v_min - 2, v_min - 1, v_max + 1, v_max + 2] {
+                mark(u, v);
+            }
+        }
+
+        infinite
+    }
+}
+
+/// smallest and largest u = x + y and v = x - y
+fn rotated_edges(coordinates: &[Vec<i32>]) -> (i32, i32, i32, i32) {
+    let u = |point: &Vec<i32>| point[0] + point[1];
+    let v = |point: &Vec<i32>| point[0] - point[1];
+    (
+        coordinates.iter().map(u).min().unwrap(),
+        coordinates.iter().map(u).max().unwrap(),
+        coordinates.iter().map(v).min().unwrap(),
+        coordinates.iter().map(v).max().unwrap(),
+    )
+}
+
+/// `sqrt(dx² * wx + dy² * wy)`, plain Euclidean when both weights are 1
+pub struct Euclidean {
+    pub wx: u32,
+    pub wy: u32,
+}
+
+impl Metric for Euclidean {
+    /// the squared distance, exact in integers
+    fn rank(&self, a: &[i32], b: &[i32]) -> u64 {
+        let (dx, dy) = (a[0].abs_diff(b[0]) as u64, a[1].abs_diff(b[1]) as u64);
+        dx * dx * self.wx as u64 + dy * dy * self.wy as u64
+    }
+
+    fn distance(&self, a: &[i32], b: &[i32]) -> f64 {
+        (self.rank(a, b) as f64).sqrt()
+    }
+
+    /// Covers the polygon of every finite area, found by cutting a huge square down with the
+    /// bisector between its coordinate and every other one. The weights stretch the axes first
+    /// so that the bisectors are the plain Euclidean ones.
+    fn extent(&self, coordinates: &[Vec<i32>], bounds: &Bounds) -> Bounds {
+        let (sx, sy) = ((self.wx as f64).sqrt(), (self.wy as f64).sqrt());
+        let points = coordinates
+            .iter()
+            .map(|point| (point[0] as f64 * sx, point[1] as f64 * sy))
+            .collect::<Vec<(f64, f64)>>();
+        let infinite = on_hull(coordinates);
+
+        let (mut top, mut bottom) = (bounds.min[1], bounds.max[1]);
+        let (mut left, mut right) = (bounds.min[0], bounds.max[0]);
+        for (idx, &site) in points.iter().enumerate() {
+            if infinite[idx] {
+                continue;
+            }
+            for (x, y) in cell(site, &points) {
+                left = left.min((x / sx).floor() as i32 - 1);
+                right = right.max((x / sx).ceil() as i32 + 1);
+                top = top.min((y / sy).floor() as i32 - 1);
+                bottom = bottom.max((y / sy).ceil() as i32 + 1);
+            }
+        }
+        Bounds {
+            min: vec![left, top],
+            max: vec![right, bottom],
+        }
+    }
+
+    /// Exactly the coordinates on the boundary of the convex hull have unbounded areas, the ones
+    /// in the middle of a hull edge included. Weights only stretch the plane, which leaves the
+    /// hull alone.
+    fn infinite(
+        &self,
+        coordinates: &[Vec<i32>],
+        _voronoi: &Voronoi,
+        _bounds: &Bounds,
+    ) -> Vec<bool> {
+        on_hull(coordinates)
+    }
+}
+
+/// whether each coordinate lies on the boundary of the convex hull of all of them
+fn on_hull(coordinates: &[Vec<i32>]) -> Vec<bool> {
+    let plane = coordinates
+        .iter()
+        .map(|point| (point[0], point[1]))
+        .collect::<Vec<(i32, i32)>>();
+    let hull = convex_hull(&plane);
+    plane
+        .iter()
+        .map(|pixel| {
+            if hull.len() < 3 {
+                // every coordinate on one line, all of them reach out sideways
+                return true;
+            }
+            (0..hull.len()).any(|idx| on_segment(hull[idx], hull[(idx + 1) % hull.len()], *pixel))
+        })
+        .collect()
+}
+
+/// Corners of the Euclidean Voronoi cell of `site`. Coordinates are tried nearest first, one
+/// more than twice as far as the farthest corner cannot cut the cell any more.
+fn cell(site: (f64, f64), points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    const FAR: f64 = 1e10;
+    let mut polygon = vec![
+        (site.0 - FAR, site.1 - FAR),
+        (site.0 + FAR, site.1 - FAR),
+        (site.0 + FAR, site.1 + FAR),
+        (site.0 - FAR, site.1 + FAR),
+    ];
+
+    let squared = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+    let mut others = points
+        .iter()
+        .copied()
+        .filter(|&other| other != site)
+        .collect::<Vec<(f64, f64)>>();
+    others.sort_by(|a, b| squared(site, *a).total_cmp(&squared(site, *b)));
+
+    for other in others {
+        let radius = polygon
+            .iter()
+            .map(|&corner| squared(site, corner))
+            .fold(0.0, f64::max);
+        if squared(site, other) > 4.0 * radius {
+            break;
+        }
+
+        // keep the side of the bisector closer to site: p . n <= c
+        let n = (other.0 - site.0, other.1 - site.1);
+        let c = (squared(other, (0.0, 0.0)) - squared(site, (0.0, 0.0))) / 2.0;
+        let side = |p: (f64, f64)| p.0 * n.0 + p.1 * n.1 - c;
+
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for idx in 0..polygon.len() {
+            let (a, b) = (polygon[idx], polygon[(idx + 1) % polygon.len()]);
+            let (sa, sb) = (side(a), side(b));
+            if sa <= 0.0 {
+                clipped.push(a);
+            }
+            if (sa < 0.0 && sb > 0.0) || (sa > 0.0 && sb < 0.0) {
+                let t = sa / (sa - sb);
+                clipped.push((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t));
+            }
+        }
+        polygon = clipped;
+    }
+
+    polygon
+}
+
+fn cross(o: (i32, i32), a: (i32, i32), b: (i32, i32)) -> i64 {
+    (a.0 - o.0) as i64 * (b.1 - o.1) as i64 - (a.1 - o.1) as i64 * (b.0 - o.0) as i64
+}
+
+fn on_segment(a: (i32, i32), b: (i32, i32), p: (i32, i32)) -> bool {
+    cross(a, b, p) == 0
+        && a.0.min(b.0) <= p.0
+        && p.0 <= a.0.max(b.0)
+        && a.1.min(b.1) <= p.1
+        && p.1 <= a.1.max(b.1)
+}
+
+/// corners of the convex hull in counter clockwise order, collinear points left out
+fn convex_hull(coordinates: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut points = coordinates.to_vec();
+    points.sort_by_key(|pixel| (pixel.0, pixel.1));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull: Vec<(i32, i32)> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.iter().rev().copied().collect()] {
+        let start = hull.len();
+        for pixel in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], pixel) <= 0
+            {
+                hull.pop();
+            }
+            hull.push(pixel);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// parse "manhattan", "chebyshev", "euclidean", or a weighted "manhattan:wx,wy,..." with one
+/// weight per axis or "euclidean:wx,wy"
+pub fn parse_metric(value: &str) -> std::result::Result<String, String> {
+    metric(value).map(|_| value.to_string())
+}
+
+pub fn metric(value: &str) -> std::result::Result<Box<dyn Metric>, String> {
+    let (name, weights) = match value.split_once(':') {
+        Some((name, weights)) => {
+            let parse = |weight: &str| match weight.trim().parse::<u32>() {
+                Ok(weight) if weight > 0 => Ok(weight),
+                _ => Err(format!("weights must be positive integers, got '{weight}'")),
+            };
+            let weights = weights
+                .split(',')
+                .map(parse)
+                .collect::<std::result::Result<Vec<u32>, String>>()?;
+            if weights.len() < 2 {
+                return Err(format!(
+                    "expected a weight for each of at least two axes but got '{value}'"
+                ));
+            }
+            (name, Some(weights))
+        }
+        None => (value, None),
+    };
+
+    match (name, weights) {
+        ("manhattan", weights) => Ok(Box::new(Manhattan {
+            weights: weights.unwrap_or_default(),
+        })),
+        ("euclidean", None) => Ok(Box::new(Euclidean { wx: 1, wy: 1 })),
+        ("euclidean", Some(weights)) if weights.len() == 2 => Ok(Box::new(Euclidean {
+            wx: weights[0],
+            wy: weights[1],
+        })),
+        ("chebyshev", None) => Ok(Box::new(Chebyshev)),
+        _ => Err(format!(
+            "expected manhattan, chebyshev, euclidean, manhattan:wx,wy,... or euclidean:wx,wy but got '{value}'"
+        )),
+    }
+}
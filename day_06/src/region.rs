@@ -0,0 +1,235 @@
+use crate::metric::Metric;
+use crate::volume::Bounds;
+
+/// Every location whose distances to all coordinates add up to less than a threshold
+pub struct Region {
+    pub size: u64,
+    /// `None` when the region is empty
+    pub bounds: Option<Bounds>,
+}
+
+/// Find the whole region, wherever it reaches. `bounds` is the bounding box of the coordinates.
+pub fn region(
+    coordinates: &[Vec<i32>],
+    bounds: &Bounds,
+    metric: &dyn Metric,
+    threshold: u64,
+) -> Region {
+    match metric.axis_weights(bounds.dims()) {
+        Some(weights) => separable(coordinates, &weights, threshold),
+        None => grown(coordinates, bounds, metric, threshold),
+    }
+}
+
+/// Whether each cell of `area` is part of the region, in the order `Bounds` numbers them
+pub fn cells(
+    coordinates: &[Vec<i32>],
+    area: &Bounds,
+    metric: &dyn Metric,
+    threshold: u64,
+) -> Vec<bool> {
+    match metric.axis_weights(area.dims()) {
+        Some(weights) => {
+            // the weighted sum of one axis for every position of the box along it
+            let sums = weights
+                .iter()
+                .enumerate()
+                .map(|(axis, &weight)| {
+                    let values = Axis::new(coordinates.iter().map(|point| point[axis]));
+                    (area.min[axis]..=area.max[axis])
+                        .map(|at| weight * values.sum(at))
+                        .collect::<Vec<u64>>()
+                })
+                .collect::<Vec<Vec<u64>>>();
+
+            (0..area.cells())
+                .map(|mut index| {
+                    let mut total = 0;
+                    for sums in &sums {
+                        total += sums[index % sums.len()];
+                        index /= sums.len();
+                    }
+                    total < threshold
+                })
+                .collect()
+        }
+        None => (0..area.cells())
+            .map(|index| within(coordinates, metric, threshold, &area.point(index)))
+            .collect(),
+    }
+}
+
+fn within(coordinates: &[Vec<i32>], metric: &dyn Metric, threshold: u64, at: &[i32]) -> bool {
+    let sum: f64 = coordinates
+        .iter()
+        .map(|point| metric.distance(at, point))
+        .sum();
+    sum < threshold as f64
+}
+
+/// When the distance is a weighted sum over the axes, `wx * |dx| + wy * |dy| + ...`, the sum
+/// over all coordinates splits into `wx * Sx(x) + wy * Sy(y) + ...`. The sums are worked out per
+/// axis for every position that can be part of the region, then every axis but the last is
+/// walked over and the last one is looked up in its sorted sums. In the plane that is
+/// O(N log N + extent log extent).
+fn separable(coordinates: &[Vec<i32>], weights: &[u64], threshold: u64) -> Region {
+    let axes = (0..weights.len())
+        .map(|axis| Axis::new(coordinates.iter().map(|point| point[axis])))
+        .collect::<Vec<Axis>>();
+    let minimums = axes
+        .iter()
+        .zip(weights)
+        .map(|(axis, &weight)| weight * axis.sum(axis.median()))
+        .collect::<Vec<u64>>();
+    let best = minimums.iter().sum::<u64>();
+    if best >= threshold {
+        return Region {
+            size: 0,
+            bounds: None,
+        };
+    }
+
+    // the positions along each axis where the region is not empty even with the best sums on
+    // all the others
+    let mut bounds = Bounds {
+        min: Vec::new(),
+        max: Vec::new(),
+    };
+    let mut sums = Vec::new();
+    for ((axis, &weight), minimum) in axes.iter().zip(weights).zip(&minimums) {
+        let (first, axis_sums) = axis.sums_below(weight, threshold - (best - minimum));
+        bounds.min.push(first);
+        bounds.max.push(first + axis_sums.len() as i32 - 1);
+        sums.push(
+            axis_sums
+                .iter()
+                .map(|&sum| weight * sum)
+                .collect::<Vec<u64>>(),
+        );
+    }
+
+    let mut last = sums.pop().unwrap();
+    last.sort_unstable();
+    Region {
+        size: count(&sums, &last, threshold),
+        bounds: Some(bounds),
+    }
+}
+
+/// ways to pick one sum per axis, the last one from `last`, adding up to less than `budget`
+fn count(sums: &[Vec<u64>], last: &[u64], budget: u64) -> u64 {
+    match sums.split_first() {
+        None => last.partition_point(|&sum| sum < budget) as u64,
+        Some((axis, rest)) => axis
+            .iter()
+            .filter(|&&sum| sum < budget)
+            .map(|&sum| count(rest, last, budget - sum))
+            .sum(),
+    }
+}
+
+/// The positions of every coordinate along one axis, sorted, with their prefix sums
+pub struct Axis {
+    sorted: Vec<i64>,
+    prefix: Vec<i64>,
+}
+
+impl Axis {
+    pub fn new(values: impl Iterator<Item = i32>) -> Self {
+        let mut sorted = values.map(|value| value as i64).collect::<Vec<i64>>();
+        sorted.sort_unstable();
+
+        let mut prefix = Vec::with_capacity(sorted.len() + 1);
+        prefix.push(0);
+        for value in &sorted {
+            prefix.push(prefix.last().unwrap() + value);
+        }
+
+        Axis { sorted, prefix }
+    }
+
+    /// where the sum of distances is smallest
+    pub fn median(&self) -> i32 {
+        self.sorted[self.sorted.len() / 2] as i32
+    }
+
+    /// sum of the distances from `at` to every position
+    pub fn sum(&self, at: i32) -> u64 {
+        let at = at as i64;
+        let below = self.sorted.partition_point(|&value| value <= at);
+        let total = *self.prefix.last().unwrap();
+        let (n, k) = (self.sorted.len() as i64, below as i64);
+
+        (at * k - self.prefix[below] + (total - self.prefix[below]) - at * (n - k)) as u64
+    }
+
+    /// The first position where `weight` times the sum drops below `limit`, and the sums from
+    /// there on for as long as they stay below. The sums are convex, so that is one run around
+    /// the median.
+    pub fn sums_below(&self, weight: u64, limit: u64) -> (i32, Vec<u64>) {
+        let median = self.median();
+
+        let mut first = median;
+        while weight * self.sum(first - 1) < limit {
+            first -= 1;
+        }
+
+        let mut sums = Vec::new();
+        let mut at = first;
+        loop {
+            let sum = self.sum(at);
+            if weight * sum >= limit {
+                break;
+            }
+            sums.push(sum);
+            at += 1;
+        }
+
+        (first, sums)
+    }
+}
+
+/// Any other metric, all of which are in the plane: the sum of distances is convex, so the
+/// region is one convex blob. Starting at the bounding box, the rectangle is grown until a ring
+/// around it holds no cell of the region, then every cell inside is checked.
+fn grown(coordinates: &[Vec<i32>], bounds: &Bounds, metric: &dyn Metric, threshold: u64) -> Region {
+    let inside = |x: i32, y: i32| within(coordinates, metric, threshold, &[x, y]);
+
+    let (mut top, mut bottom, mut left, mut right) =
+        (bounds.min[1], bounds.max[1], bounds.min[0], bounds.max[0]);
+    loop {
+        let (t, b, l, r) = (top - 1, bottom + 1, left - 1, right + 1);
+        let ring = (l..=r).any(|x| inside(x, t) || inside(x, b))
+            || (t..=b).any(|y| inside(l, y) || inside(r, y));
+        if !ring {
+            break;
+        }
+        (top, bottom, left, right) = (t, b, l, r);
+    }
+
+    let mut size = 0;
+    let mut found: Option<Bounds> = None;
+    for y in top..=bottom {
+        for x in left..=right {
+            if !inside(x, y) {
+                continue;
+            }
+            size += 1;
+            found = Some(match found {
+                Some(found) => Bounds {
+                    min: vec![found.min[0].min(x), found.min[1].min(y)],
+                    max: vec![found.max[0].max(x), found.max[1].max(y)],
+                },
+                None => Bounds {
+                    min: vec![x, y],
+                    max: vec![x, y],
+                },
+            });
+        }
+    }
+
+    Region {
+        size,
+        bounds: found,
+    }
+}
@@ -0,0 +1,302 @@
+use gif::{Encoder, Frame, Repeat};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+
+use crate::metric::Metric;
+use crate::volume::Bounds;
+use crate::voronoi::Voronoi;
+
+/// most pixels drawn over all frames, one byte each
+const MAX_PIXELS: u64 = 1 << 28;
+
+// palette indices
+const TIE: u8 = 0;
+const MARKER: u8 = 1;
+const OUTLINE: u8 = 2;
+const OUTSIDE: u8 = 3;
+const INSIDE: u8 = 4;
+/// first index of the colors given to areas
+const AREAS: u8 = 5;
+
+/// RGB palette: [tie, marker, outline, outside the region, inside the region, area colors...]
+fn palette(areas: usize) -> Vec<u8> {
+    let mut palette = vec![
+        0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0x80, 0x80, 0x80, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00,
+    ];
+
+    // hues spread by the golden angle stay apart however many are needed
+    for idx in 0..areas {
+        let hue = (idx as f64 * 0.618_033_988_75).fract() * 6.0;
+        let (value, saturation) = (0.9, 0.65);
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        for channel in [r, g, b] {
+            palette.push(((channel + m) * 255.0) as u8);
+        }
+    }
+
+    palette
+}
+
+/// Draw every area in its own color with ties in black, the bounding box `bounds` of the
+/// coordinates in gray and the coordinates themselves in white. Areas only need a color
+/// different from the ones they touch, so the palette stays small whatever the number of
+/// coordinates. Past two axes there is one frame for every slice across the third axis and
+/// beyond, and only the first two axes are padded.
+pub fn areas(
+    coordinates: &[Vec<i32>],
+    bounds: &Bounds,
+    metric: &dyn Metric,
+    output: &str,
+    padding: u32,
+    scale: u32,
+) -> crate::Result<()> {
+    let padded = bounds.grown(0..2, padding as i32);
+    check_size(&padded, scale)?;
+    let voronoi = Voronoi::new(coordinates, padded, metric);
+    let padded = &voronoi.bounds;
+
+    // areas side by side or only apart by a line of ties, each pair once
+    let closest = |cell: usize| match voronoi.owner_of(cell) {
+        Some(owner) => vec![owner],
+        None => voronoi
+            .tied_of(cell)
+            .iter()
+            .map(|&idx| idx as usize)
+            .collect(),
+    };
+    let mut touching: HashSet<(usize, usize)> = HashSet::new();
+    for cell in 0..padded.cells() {
+        let point = padded.point(cell);
+        let mut near = closest(cell);
+        // one further along every axis, and along both axes of the slice at once
+        let mut diagonal = point.clone();
+        diagonal[0] += 1;
+        diagonal[1] += 1;
+        let nexts = (0..point.len())
+            .map(|axis| {
+                let mut next = point.clone();
+                next[axis] += 1;
+                next
+            })
+            .chain([diagonal]);
+        for next in nexts {
+            if let Some(next) = padded.index(&next) {
+                near.extend(closest(next));
+            }
+        }
+        near.sort_unstable();
+        near.dedup();
+        for (idx, &a) in near.iter().enumerate() {
+            for &b in &near[idx + 1..] {
+                touching.insert((a, b));
+            }
+        }
+    }
+
+    let (colors, used) = colors(coordinates.len(), &touching);
+
+    let mut cells = Vec::with_capacity(padded.cells());
+    for cell in 0..padded.cells() {
+        let point = padded.point(cell);
+        let (x, y) = (point[0], point[1]);
+        let on_edge = (x == bounds.min[0] || x == bounds.max[0])
+            && (bounds.min[1]..=bounds.max[1]).contains(&y)
+            || (y == bounds.min[1] || y == bounds.max[1])
+                && (bounds.min[0]..=bounds.max[0]).contains(&x);
+        cells.push(match voronoi.owner_of(cell) {
+            _ if on_edge => OUTLINE,
+            Some(owner) => colors[owner],
+            None => TIE,
+        });
+    }
+    mark(&mut cells, coordinates, padded);
+
+    let frames = slices(&cells, padded);
+    write(output, &frames, padded.size(0), scale, &palette(used))
+}
+
+/// Draw the locations whose distances add up to less than `threshold` in black on white, with
+/// the coordinates in gray, one frame for every slice like `areas`
+pub fn region(
+    coordinates: &[Vec<i32>],
+    bounds: &Bounds,
+    metric: &dyn Metric,
+    threshold: u64,
+    output: &str,
+    padding: u32,
+    scale: u32,
+) -> crate::Result<()> {
+    let padded = bounds.grown(0..2, padding as i32);
+    check_size(&padded, scale)?;
+
+    let mut cells = crate::region::cells(coordinates, &padded, metric, threshold)
+        .into_iter()
+        .map(|inside| if inside { INSIDE } else { OUTSIDE })
+        .collect::<Vec<u8>>();
+    for point in coordinates {
+        cells[padded.index(point).unwrap()] = OUTLINE;
+    }
+
+    let frames = slices(&cells, &padded);
+    write(output, &frames, padded.size(0), scale, &palette(0))
+}
+
+/// Fail before anything is labelled or drawn when the image of `padded`, `scale`x`scale` pixels
+/// per cell, is too large
+fn check_size(padded: &Bounds, scale: u32) -> crate::Result<()> {
+    if scale == 0 {
+        return Err("scale must be at least 1".into());
+    }
+
+    let scale = scale as u64;
+    let (width, height) = (padded.size(0) as u64 * scale, padded.size(1) as u64 * scale);
+    let slices = (2..padded.dims()).try_fold(1u64, |slices, axis| {
+        slices.checked_mul(padded.size(axis) as u64)
+    });
+    let pixels = slices
+        .and_then(|slices| slices.checked_mul(width))
+        .and_then(|pixels| pixels.checked_mul(height));
+    if pixels.is_none_or(|pixels| pixels > MAX_PIXELS) {
+        let frames = match slices {
+            Some(1) => String::new(),
+            Some(slices) => format!(" in each of {slices} slices"),
+            None => " in each of too many slices".to_string(),
+        };
+        return Err(format!(
+            "{width}x{height} pixels{frames} is too large to draw, try a smaller --scale or --padding"
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// cut the cells of a box into one frame per slice across the first two axes
+fn slices(cells: &[u8], bounds: &Bounds) -> Vec<Vec<u8>> {
+    cells
+        .chunks(bounds.size(0) * bounds.size(1))
+        .map(|slice| slice.to_vec())
+        .collect()
+}
+
+/// Greedy coloring: every area gets the first color none of the areas it touches has, wrapping
+/// around in the unlikely case the palette runs out. Returns the palette index of every area and
+/// the number of colors used.
+fn colors(count: usize, touching: &HashSet<(usize, usize)>) -> (Vec<u8>, usize) {
+    let mut neighbours = vec![Vec::new(); count];
+    for &(a, b) in touching {
+        neighbours[a].push(b);
+        neighbours[b].push(a);
+    }
+
+    let available = 256 - AREAS as usize;
+    let mut colors: Vec<Option<usize>> = vec![None; count];
+    for idx in 0..count {
+        let taken = neighbours[idx]
+            .iter()
+            .filter_map(|&other| colors[other])
+            .collect::<HashSet<usize>>();
+        colors[idx] = Some((0..).find(|color| !taken.contains(color)).unwrap() % available);
+    }
+    let used = colors.iter().flatten().max().map_or(0, |&max| max + 1);
+
+    let colors = colors
+        .into_iter()
+        .map(|color| AREAS + color.unwrap() as u8)
+        .collect();
+    (colors, used)
+}
+
+/// a small cross on every coordinate, in the slice it is in
+fn mark(cells: &mut [u8], coordinates: &[Vec<i32>], bounds: &Bounds) {
+    for point in coordinates {
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let mut at = point.clone();
+            at[0] += dx;
+            at[1] += dy;
+            if let Some(index) = bounds.index(&at) {
+                cells[index] = MARKER;
+            }
+        }
+    }
+}
+
+/// Blow every cell up to `scale`x`scale` pixels and write them as a .gif or .ppm. Several
+/// frames become an animation in a gif and a sequence of images in a ppm.
+fn write(
+    output: &str,
+    frames: &[Vec<u8>],
+    cols: usize,
+    scale: u32,
+    palette: &[u8],
+) -> crate::Result<()> {
+    // `check_size` made sure all of this fits
+    let rows = frames[0].len() / cols;
+    let (width, height) = (cols * scale as usize, rows * scale as usize);
+    let frames = frames
+        .iter()
+        .map(|cells| {
+            let mut pixels = Vec::with_capacity(width * height);
+            for row in cells.chunks(cols) {
+                let mut line = Vec::with_capacity(width);
+                for &cell in row {
+                    line.extend(std::iter::repeat_n(cell, scale as usize));
+                }
+                for _ in 0..scale {
+                    pixels.extend_from_slice(&line);
+                }
+            }
+            pixels
+        })
+        .collect::<Vec<Vec<u8>>>();
+
+    if output.ends_with(".gif") {
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(format!("{width}x{height} is too large for a gif, try a .ppm").into());
+        }
+        let mut image = fs::File::create(output)?;
+        let mut encoder = Encoder::new(&mut image, width as u16, height as u16, palette)?;
+        if frames.len() > 1 {
+            encoder.set_repeat(Repeat::Infinite)?;
+        }
+        for pixels in &frames {
+            let frame = Frame {
+                width: width as u16,
+                height: height as u16,
+                buffer: Cow::Borrowed(pixels),
+                // half a second per slice
+                delay: if frames.len() > 1 { 50 } else { 0 },
+                ..Frame::default()
+            };
+            encoder.write_frame(&frame)?;
+        }
+    } else if output.ends_with(".ppm") {
+        let mut image = std::io::BufWriter::new(fs::File::create(output)?);
+        for pixels in &frames {
+            write!(image, "P6\n{width} {height}\n255\n")?;
+            for &pixel in pixels {
+                let idx = pixel as usize * 3;
+                image.write_all(&palette[idx..idx + 3])?;
+            }
+        }
+        image.flush()?;
+    } else {
+        return Err(format!("'{output}' should end in .gif or .ppm").into());
+    }
+
+    println!("Successfully written file {output}");
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::fmt::Write;
+
+use crate::voronoi::Voronoi;
+
+/// Everything known about the locations closest to one coordinate
+#[derive(Debug)]
+pub struct Area {
+    pub coordinate: Vec<i32>,
+    /// cells closest to this coordinate alone, only meaningful when the area is finite
+    pub cells: u64,
+    pub infinite: bool,
+    /// cells this coordinate shares with others that are just as close, inside the labelled box
+    pub ties: u64,
+    /// 1 for the largest finite area, equal areas share a rank. Infinite areas have none.
+    pub rank: Option<usize>,
+}
+
+/// Count every area over the cells `voronoi` labelled, which hold all finite areas
+pub fn areas(coordinates: &[Vec<i32>], voronoi: &Voronoi, infinite: &[bool]) -> Vec<Area> {
+    let mut cells = vec![0u64; coordinates.len()];
+    let mut ties = vec![0u64; coordinates.len()];
+
+    for cell in 0..voronoi.bounds.cells() {
+        match voronoi.owner_of(cell) {
+            Some(owner) => cells[owner] += 1,
+            None => {
+                for &idx in voronoi.tied_of(cell) {
+                    ties[idx as usize] += 1;
+                }
+            }
+        }
+    }
+
+    let mut areas = coordinates
+        .iter()
+        .enumerate()
+        .map(|(idx, coordinate)| Area {
+            coordinate: coordinate.clone(),
+            cells: cells[idx],
+            infinite: infinite[idx],
+            ties: ties[idx],
+            rank: None,
+        })
+        .collect::<Vec<Area>>();
+
+    let mut finite = areas
+        .iter()
+        .filter(|area| !area.infinite)
+        .map(|area| area.cells)
+        .collect::<Vec<u64>>();
+    finite.sort_unstable_by(|a, b| b.cmp(a));
+    for area in areas.iter_mut().filter(|area| !area.infinite) {
+        area.rank = Some(finite.partition_point(|&cells| cells > area.cells) + 1);
+    }
+
+    areas
+}
+
+/// one line per coordinate, largest finite areas first and infinite ones last
+pub fn table(areas: &[Area]) -> String {
+    let mut order = areas.iter().collect::<Vec<&Area>>();
+    order.sort_by_key(|area| (area.rank.unwrap_or(usize::MAX), area.coordinate.clone()));
+
+    let mut table = String::from("rank  coordinate      area      ties\n");
+    for area in order {
+        let rank = area.rank.map_or("-".to_string(), |rank| rank.to_string());
+        let coordinate = area
+            .coordinate
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let cells = if area.infinite {
+            "infinite".to_string()
+        } else {
+            area.cells.to_string()
+        };
+        writeln!(
+            table,
+            "{:<5} {:<15} {:>8} {:>9}",
+            rank, coordinate, cells, area.ties
+        )
+        .unwrap();
+    }
+    table
+}
+
+/// the areas in input order as a JSON array
+pub fn json(areas: &[Area]) -> String {
+    let mut json = String::from("[\n");
+    for (idx, area) in areas.iter().enumerate() {
+        let cells = if area.infinite {
+            "null".to_string()
+        } else {
+            area.cells.to_string()
+        };
+        let rank = area
+            .rank
+            .map_or("null".to_string(), |rank| rank.to_string());
+        json.push_str("  {");
+        for (axis, value) in area.coordinate.iter().enumerate() {
+            write!(json, "\"{}\": {}, ", axis_name(axis), value).unwrap();
+        }
+        write!(
+            json,
+            "\"area\": {}, \"infinite\": {}, \"ties\": {}, \"rank\": {}}}",
+            cells, area.infinite, area.ties, rank
+        )
+        .unwrap();
+        json.push_str(if idx + 1 < areas.len() { ",\n" } else { "\n" });
+    }
+    json.push_str("]\n");
+    json
+}
+
+/// x, y, z and w, then numbered from the fifth axis on
+pub fn axis_name(axis: usize) -> String {
+    match "xyzw".chars().nth(axis) {
+        Some(name) => name.to_string(),
+        None => format!("axis{}", axis + 1),
+    }
+}
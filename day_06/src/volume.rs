@@ -0,0 +1,69 @@
+/// A box with any number of axes, from `min` to `max` inclusive on each. Cells are numbered
+/// with the first axis changing fastest, so every slice across the last axes is one run.
+#[derive(Debug, Clone)]
+pub struct Bounds {
+    pub min: Vec<i32>,
+    pub max: Vec<i32>,
+}
+
+impl Bounds {
+    /// the smallest box holding every point
+    pub fn around(points: &[Vec<i32>]) -> Self {
+        let dims = points[0].len();
+        Bounds {
+            min: (0..dims)
+                .map(|axis| points.iter().map(|point| point[axis]).min().unwrap())
+                .collect(),
+            max: (0..dims)
+                .map(|axis| points.iter().map(|point| point[axis]).max().unwrap())
+                .collect(),
+        }
+    }
+
+    /// grown by `by` on both sides of the given axes
+    pub fn grown(&self, axes: std::ops::Range<usize>, by: i32) -> Self {
+        let mut grown = self.clone();
+        for axis in axes {
+            grown.min[axis] -= by;
+            grown.max[axis] += by;
+        }
+        grown
+    }
+
+    pub fn dims(&self) -> usize {
+        self.min.len()
+    }
+
+    pub fn size(&self, axis: usize) -> usize {
+        self.max[axis].abs_diff(self.min[axis]) as usize + 1
+    }
+
+    pub fn cells(&self) -> usize {
+        (0..self.dims()).map(|axis| self.size(axis)).product()
+    }
+
+    pub fn index(&self, point: &[i32]) -> Option<usize> {
+        let mut index = 0;
+        for axis in (0..self.dims()).rev() {
+            if point[axis] < self.min[axis] || point[axis] > self.max[axis] {
+                return None;
+            }
+            index = index * self.size(axis) + (point[axis] - self.min[axis]) as usize;
+        }
+        Some(index)
+    }
+
+    pub fn point(&self, mut index: usize) -> Vec<i32> {
+        let mut point = Vec::with_capacity(self.dims());
+        for axis in 0..self.dims() {
+            point.push(self.min[axis] + (index % self.size(axis)) as i32);
+            index /= self.size(axis);
+        }
+        point
+    }
+
+    /// true on the outermost layer of cells
+    pub fn on_shell(&self, point: &[i32]) -> bool {
+        (0..self.dims()).any(|axis| point[axis] == self.min[axis] || point[axis] == self.max[axis])
+    }
+}
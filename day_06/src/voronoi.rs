@@ -0,0 +1,206 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::metric::Metric;
+use crate::volume::Bounds;
+
+const UNVISITED: u32 = u32::MAX;
+/// more than one coordinate is closest
+const TIE: u32 = u32::MAX - 1;
+
+/// Every cell of a box labelled with the coordinate closest to it. When the metric is made of
+/// grid steps it is built with a flood fill that starts from all coordinates at once, so the
+/// cost only depends on the size of the box and not on the number of coordinates.
+pub struct Voronoi {
+    pub bounds: Bounds,
+    owners: Vec<u32>,
+    /// the coordinates that are equally close, for every tied cell
+    ties: HashMap<usize, Vec<u32>>,
+}
+
+impl Voronoi {
+    /// Label every cell of `bounds`, which has to contain every coordinate. Inside such a box
+    /// the cheapest path over the metric's steps is exactly the distance.
+    pub fn new(coordinates: &[Vec<i32>], bounds: Bounds, metric: &dyn Metric) -> Self {
+        for point in coordinates {
+            assert!(
+                bounds.index(point).is_some(),
+                "{point:?} is outside the area being labelled"
+            );
+        }
+
+        let (owners, ties) = match metric.steps(bounds.dims()) {
+            Some(steps) => flood(coordinates, &bounds, &steps),
+            None => compare(coordinates, &bounds, metric),
+        };
+
+        Voronoi {
+            bounds,
+            owners,
+            ties,
+        }
+    }
+
+    /// index of the coordinate closest to `at`, `None` on a tie or outside the box
+    pub fn owner(&self, at: &[i32]) -> Option<usize> {
+        self.owner_of(self.bounds.index(at)?)
+    }
+
+    /// `owner` of the cell numbered `cell` in `bounds`
+    pub fn owner_of(&self, cell: usize) -> Option<usize> {
+        match self.owners[cell] {
+            TIE | UNVISITED => None,
+            owner => Some(owner as usize),
+        }
+    }
+
+    /// indices of the coordinates equally close to the cell numbered `cell` in `bounds`, empty
+    /// unless it is a tie
+    pub fn tied_of(&self, cell: usize) -> &[u32] {
+        self.ties.get(&cell).map_or(&[], |tied| tied.as_slice())
+    }
+}
+
+/// Cheapest distance first, so every cell that can reach another one more cheaply is settled
+/// before it. A cell reached at the same distance from two different owners, or from a tie, is
+/// a tie.
+fn flood(
+    coordinates: &[Vec<i32>],
+    bounds: &Bounds,
+    steps: &[(Vec<i32>, u64)],
+) -> (Vec<u32>, HashMap<usize, Vec<u32>>) {
+    let cells = bounds.cells();
+    let mut distances = vec![u64::MAX; cells];
+    let mut owners = vec![UNVISITED; cells];
+    let mut ties: HashMap<usize, Vec<u32>> = HashMap::new();
+    let mut queue: BinaryHeap<Reverse<(u64, usize)>> = BinaryHeap::new();
+
+    for (idx, point) in coordinates.iter().enumerate() {
+        let cell = bounds.index(point).unwrap();
+        if distances[cell] == 0 {
+            // two coordinates on the same spot are as close as each other
+            let first = owners[cell];
+            let tied = ties.entry(cell).or_default();
+            if tied.is_empty() {
+                tied.push(first);
+            }
+            tied.push(idx as u32);
+            owners[cell] = TIE;
+        } else {
+            distances[cell] = 0;
+            owners[cell] = idx as u32;
+            queue.push(Reverse((0, cell)));
+        }
+    }
+
+    // how far apart the two ends of every step are in the numbering of the cells
+    let sizes = (0..bounds.dims())
+        .map(|axis| bounds.size(axis))
+        .collect::<Vec<usize>>();
+    let mut strides = Vec::with_capacity(sizes.len());
+    let mut stride = 1;
+    for &size in &sizes {
+        strides.push(stride as isize);
+        stride *= size;
+    }
+    let jumps = steps
+        .iter()
+        .map(|(offset, _)| {
+            offset
+                .iter()
+                .zip(&strides)
+                .map(|(&by, &stride)| by as isize * stride)
+                .sum::<isize>()
+        })
+        .collect::<Vec<isize>>();
+
+    // position of the current cell along every axis, counted from the low side of the box
+    let mut at = vec![0; sizes.len()];
+    while let Some(Reverse((distance, cell))) = queue.pop() {
+        if distance > distances[cell] {
+            // reached more cheaply since it was queued
+            continue;
+        }
+        let mut rest = cell;
+        for (axis, &size) in sizes.iter().enumerate() {
+            at[axis] = (rest % size) as i64;
+            rest /= size;
+        }
+
+        for ((offset, cost), &jump) in steps.iter().zip(&jumps) {
+            let outside = offset
+                .iter()
+                .zip(&at)
+                .zip(&sizes)
+                .any(|((&by, &at), &size)| !(0..size as i64).contains(&(at + by as i64)));
+            if outside {
+                continue;
+            }
+            let next = cell.wrapping_add_signed(jump);
+            let next_distance = distance + cost;
+
+            if next_distance < distances[next] {
+                distances[next] = next_distance;
+                owners[next] = owners[cell];
+                ties.remove(&next);
+                if owners[cell] == TIE {
+                    let tied = ties[&cell].clone();
+                    ties.insert(next, tied);
+                }
+                queue.push(Reverse((next_distance, next)));
+            } else if next_distance == distances[next] && owners[next] != owners[cell] {
+                // everyone closest to either cell is closest to this one
+                let mut tied = match owners[next] {
+                    TIE => ties.remove(&next).unwrap(),
+                    owner => vec![owner],
+                };
+                match owners[cell] {
+                    TIE => tied.extend(&ties[&cell]),
+                    owner => tied.push(owner),
+                }
+                tied.sort_unstable();
+                tied.dedup();
+                owners[next] = TIE;
+                ties.insert(next, tied);
+            } else if next_distance == distances[next] && owners[cell] == TIE {
+                // both tied, possibly between different coordinates
+                let extra = ties[&cell].clone();
+                let tied = ties.get_mut(&next).unwrap();
+                tied.extend(extra);
+                tied.sort_unstable();
+                tied.dedup();
+            }
+        }
+    }
+
+    (owners, ties)
+}
+
+/// For metrics that are not made of grid steps, every coordinate is compared for every cell
+fn compare(
+    coordinates: &[Vec<i32>],
+    bounds: &Bounds,
+    metric: &dyn Metric,
+) -> (Vec<u32>, HashMap<usize, Vec<u32>>) {
+    let mut owners = Vec::with_capacity(bounds.cells());
+    let mut ties: HashMap<usize, Vec<u32>> = HashMap::new();
+    for cell in 0..bounds.cells() {
+        let at = bounds.point(cell);
+        let mut closest = (u64::MAX, Vec::new());
+        for (idx, point) in coordinates.iter().enumerate() {
+            let rank = metric.rank(&at, point);
+            if rank < closest.0 {
+                closest = (rank, vec![idx as u32]);
+            } else if rank == closest.0 {
+                closest.1.push(idx as u32);
+            }
+        }
+        if closest.1.len() == 1 {
+            owners.push(closest.1[0]);
+        } else {
+            ties.insert(owners.len(), closest.1);
+            owners.push(TIE);
+        }
+    }
+    (owners, ties)
+}
@@ -0,0 +1,129 @@
+use crate::fleet::{Policy, Worker};
+use crate::graph::Graph;
+use crate::schedule;
+
+/// When every task can start with as many workers as needed, and how long it can wait without
+/// holding everything up
+pub struct Critical {
+    /// earliest start of every task, once all its requirements are done
+    pub earliest: Vec<u64>,
+    /// latest start of every task that still finishes everything in `length`
+    pub latest: Vec<u64>,
+    /// the longest chain of durations, no number of workers gets everything done sooner
+    pub length: u64,
+    /// a chain of tasks taking `length`, none of which can wait
+    pub path: Vec<usize>,
+}
+
+impl Critical {
+    /// `order` has every task after its requirements, as `Graph::order` gives
+    pub fn new(graph: &Graph, order: &[usize], durations: &[u64]) -> Self {
+        let mut earliest = vec![0; graph.len()];
+        for &id in order {
+            earliest[id] = graph
+                .requirements(id)
+                .iter()
+                .map(|&before| earliest[before] + durations[before])
+                .max()
+                .unwrap_or(0);
+        }
+        let length = (0..graph.len())
+            .map(|id| earliest[id] + durations[id])
+            .max()
+            .unwrap_or(0);
+
+        let mut latest = vec![0; graph.len()];
+        for &id in order.iter().rev() {
+            let finish = graph
+                .dependents(id)
+                .iter()
+                .map(|&after| latest[after])
+                .min()
+                .unwrap_or(length);
+            latest[id] = finish - durations[id];
+        }
+
+        // from a task that starts at once and cannot wait, keep following a dependent that
+        // starts the moment it is done and cannot wait either
+        let critical = |id: usize| earliest[id] == latest[id];
+        let mut path = Vec::new();
+        let mut next = order
+            .iter()
+            .copied()
+            .find(|&id| critical(id) && earliest[id] == 0);
+        while let Some(id) = next {
+            path.push(id);
+            let done = earliest[id] + durations[id];
+            next = graph
+                .dependents(id)
+                .iter()
+                .copied()
+                .find(|&after| critical(after) && earliest[after] == done);
+        }
+
+        Critical {
+            earliest,
+            latest,
+            length,
+            path,
+        }
+    }
+
+    /// how long a task can start after its earliest start without making everything take longer
+    pub fn slack(&self, id: usize) -> u64 {
+        self.latest[id] - self.earliest[id]
+    }
+
+    /// No schedule is done sooner than the critical path, nor sooner than all the work shared out
+    /// perfectly between workers whose speeds add up to `capacity`
+    pub fn lower_bound(&self, durations: &[u64], capacity: f64) -> u64 {
+        let total = durations.iter().sum::<u64>();
+        self.length.max((total as f64 / capacity).ceil() as u64)
+    }
+
+    /// The most tasks running at once when every task starts as early as it can
+    pub fn width(&self, durations: &[u64]) -> usize {
+        // ends before starts at the same second, since an end frees its worker for the start
+        let mut events = Vec::with_capacity(2 * durations.len());
+        for (id, &duration) in durations.iter().enumerate() {
+            events.push((self.earliest[id], 1));
+            events.push((self.earliest[id] + duration, -1));
+        }
+        events.sort_unstable();
+
+        let (mut running, mut most) = (0i64, 0i64);
+        for (_, change) in events {
+            running += change;
+            most = most.max(running);
+        }
+        most as usize
+    }
+}
+
+/// The fewest workers for which the greedy schedule takes no longer than the critical path, with
+/// workers alike at normal speed that can do every task.
+/// Adding a worker can make the greedy schedule slower, so every count is tried in turn from the
+/// least that could possibly do it. With as many workers as tasks ever run at once every task
+/// starts as early as it can, so the sweep stops there at the latest.
+pub fn minimum_workers(
+    graph: &Graph,
+    ranks: &[usize],
+    durations: &[u64],
+    critical: &Critical,
+    policy: &dyn Policy,
+) -> usize {
+    if graph.len() == 0 {
+        return 0;
+    }
+
+    let total = durations.iter().sum::<u64>();
+    let least = total.div_ceil(critical.length) as usize;
+    let width = critical.width(durations);
+    (least..width)
+        .find(|&workers| {
+            let fleet = Worker::plain(workers);
+            schedule::simulate(graph, ranks, durations, &[], &fleet, policy).makespan
+                == critical.length
+        })
+        .unwrap_or(width)
+}
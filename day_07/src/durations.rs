@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::graph::Graph;
+
+/// How long every task takes: `base + scale * cost`, where the cost of a task comes from an
+/// override when there is one and from `cost` otherwise
+pub struct Durations {
+    pub base: u64,
+    pub scale: f64,
+    overrides: HashMap<String, u64>,
+    cost: fn(&str) -> Option<u64>,
+}
+
+/// The puzzle's cost: A takes 1, B takes 2... Z takes 26. Other names have none.
+pub fn letter(name: &str) -> Option<u64> {
+    match name.as_bytes() {
+        [letter @ b'A'..=b'Z'] => Some((letter - b'A' + 1) as u64),
+        _ => None,
+    }
+}
+
+impl Durations {
+    pub fn new(base: u64, scale: f64, cost: fn(&str) -> Option<u64>) -> Self {
+        Durations {
+            base,
+            scale,
+            overrides: HashMap::new(),
+            cost,
+        }
+    }
+
+    /// Read the cost of tasks from a file, one `name cost` per line. Blank lines and lines
+    /// starting with `#` are skipped.
+    pub fn read_overrides(&mut self, text: &str) -> crate::Result<()> {
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, cost) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("line {}: expected 'name cost'", idx + 1))?;
+            let cost = cost
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| format!("line {}: {}", idx + 1, e))?;
+            self.overrides.insert(name.to_string(), cost);
+        }
+        Ok(())
+    }
+
+    /// seconds `name` takes, rounded up to whole seconds
+    pub fn of(&self, name: &str) -> crate::Result<u64> {
+        let cost = match self.overrides.get(name) {
+            Some(&cost) => cost,
+            None => {
+                (self.cost)(name).ok_or_else(|| format!("no duration known for task '{name}'"))?
+            }
+        };
+        Ok(self.base + (self.scale * cost as f64).ceil() as u64)
+    }
+
+    /// the duration of every task of the graph, by id. Every task has to take some time.
+    pub fn all(&self, graph: &Graph) -> crate::Result<Vec<u64>> {
+        (0..graph.len())
+            .map(|id| {
+                let duration = self.of(graph.name(id))?;
+                if duration == 0 {
+                    return Err(format!("task '{}' would take no time", graph.name(id)).into());
+                }
+                Ok(duration)
+            })
+            .collect()
+    }
+}
+
+/// parse a scale that is a finite number of at least 0
+pub fn parse_scale(value: &str) -> std::result::Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(scale) if scale.is_finite() && scale >= 0.0 => Ok(scale),
+        _ => Err(format!("expected a number of at least 0 but got '{value}'")),
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::Graph;
+
+/// Someone or something that does tasks, `speed` times as fast as the durations say, and only
+/// tasks whose skills it all has
+#[derive(Debug, Clone)]
+pub struct Worker {
+    pub name: String,
+    pub speed: f64,
+    pub skills: HashSet<String>,
+}
+
+impl Worker {
+    /// `count` workers as in the puzzle: all alike, at normal speed and without skills
+    pub fn plain(count: usize) -> Vec<Worker> {
+        (1..=count)
+            .map(|idx| Worker {
+                name: format!("Worker {idx}"),
+                speed: 1.0,
+                skills: HashSet::new(),
+            })
+            .collect()
+    }
+
+    /// seconds this worker takes for a task of `duration`, rounded up
+    pub fn time(&self, duration: u64) -> u64 {
+        if self.speed == 1.0 {
+            duration
+        } else {
+            (duration as f64 / self.speed).ceil() as u64
+        }
+    }
+
+    pub fn can_do(&self, needs: &[String]) -> bool {
+        needs.iter().all(|skill| self.skills.contains(skill))
+    }
+}
+
+/// Read workers, one `name speed skill...` per line. Blank lines and lines starting with `#` are
+/// skipped.
+pub fn parse_fleet(text: &str) -> crate::Result<Vec<Worker>> {
+    let mut fleet = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap();
+        let speed = words
+            .next()
+            .ok_or_else(|| format!("line {}: expected 'name speed skill...'", idx + 1))?;
+        let speed = match speed.parse::<f64>() {
+            Ok(speed) if speed.is_finite() && speed > 0.0 => speed,
+            _ => {
+                return Err(format!(
+                    "line {}: speed must be a positive number, got '{}'",
+                    idx + 1,
+                    speed
+                )
+                .into())
+            }
+        };
+        fleet.push(Worker {
+            name: name.to_string(),
+            speed,
+            skills: words.map(|skill| skill.to_string()).collect(),
+        });
+    }
+    if fleet.is_empty() {
+        return Err("no workers".into());
+    }
+    Ok(fleet)
+}
+
+/// Read the skills tasks need, one `name skill...` per line. Blank lines and lines starting with
+/// `#` are skipped.
+pub fn parse_skills(text: &str) -> crate::Result<HashMap<String, Vec<String>>> {
+    let mut skills = HashMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap();
+        skills.insert(
+            name.to_string(),
+            words.map(|skill| skill.to_string()).collect(),
+        );
+    }
+    Ok(skills)
+}
+
+/// The skills every task of the graph needs, by id. Fails if no worker has all of them, since the
+/// task could never be done.
+pub fn needs(
+    graph: &Graph,
+    skills: &HashMap<String, Vec<String>>,
+    fleet: &[Worker],
+) -> crate::Result<Vec<Vec<String>>> {
+    (0..graph.len())
+        .map(|id| {
+            let needs = skills.get(graph.name(id)).cloned().unwrap_or_default();
+            if !fleet.iter().any(|worker| worker.can_do(&needs)) {
+                return Err(format!(
+                    "no worker can do task '{}', it needs {}",
+                    graph.name(id),
+                    needs.join(" ")
+                )
+                .into());
+            }
+            Ok(needs)
+        })
+        .collect()
+}
+
+/// A way of picking which of the ready tasks an idle worker gets
+pub trait Policy {
+    fn name(&self) -> &'static str;
+
+    /// Ready tasks are handed out smallest key first. `rank` is the task's place in the
+    /// priority then alphabetical order.
+    fn key(&self, duration: u64, rank: usize) -> (u64, usize);
+}
+
+/// The puzzle's rule: by priority, then alphabetically
+pub struct Alphabetical;
+
+impl Policy for Alphabetical {
+    fn name(&self) -> &'static str {
+        "alphabetical"
+    }
+
+    fn key(&self, _duration: u64, rank: usize) -> (u64, usize) {
+        (0, rank)
+    }
+}
+
+/// The task that takes longest first, so no long task is left for the end
+pub struct LongestFirst;
+
+impl Policy for LongestFirst {
+    fn name(&self) -> &'static str {
+        "longest-first"
+    }
+
+    fn key(&self, duration: u64, rank: usize) -> (u64, usize) {
+        (u64::MAX - duration, rank)
+    }
+}
+
+/// The quickest task first, so as many as possible get done early
+pub struct ShortestFirst;
+
+impl Policy for ShortestFirst {
+    fn name(&self) -> &'static str {
+        "shortest-first"
+    }
+
+    fn key(&self, duration: u64, rank: usize) -> (u64, usize) {
+        (duration, rank)
+    }
+}
+
+pub fn all() -> Vec<Box<dyn Policy>> {
+    vec![
+        Box::new(Alphabetical),
+        Box::new(LongestFirst),
+        Box::new(ShortestFirst),
+    ]
+}
+
+/// the policy with this name
+pub fn policy(name: &str) -> std::result::Result<Box<dyn Policy>, String> {
+    all()
+        .into_iter()
+        .find(|policy| policy.name() == name)
+        .ok_or_else(|| {
+            let names = all()
+                .iter()
+                .map(|policy| policy.name())
+                .collect::<Vec<&str>>()
+                .join(", ");
+            format!("expected one of {names} but got '{name}'")
+        })
+}
+
+/// parse a policy name
+pub fn parse_policy(value: &str) -> std::result::Result<String, String> {
+    policy(value).map(|_| value.to_string())
+}
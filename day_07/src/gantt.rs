@@ -0,0 +1,234 @@
+use std::fs;
+use std::io::{BufWriter, Write};
+
+use crate::graph::Graph;
+use crate::schedule::Schedule;
+
+/// Print the schedule like the puzzle's table: what every worker is doing and which tasks are
+/// done. Instead of one row per second there is one for every second something starts or ends.
+pub fn table(graph: &Graph, schedule: &Schedule) {
+    let timelines = schedule.timelines();
+
+    let mut times = schedule
+        .assignments
+        .iter()
+        .flat_map(|assignment| [assignment.start, assignment.end])
+        .collect::<Vec<u64>>();
+    times.push(0);
+    times.sort_unstable();
+    times.dedup();
+
+    let mut finished = schedule.assignments.clone();
+    finished.sort_by_key(|assignment| assignment.end);
+
+    let second = "Second".len().max(schedule.makespan.to_string().len());
+    let column = schedule
+        .workers
+        .iter()
+        .map(|name| name.chars().count())
+        .chain((0..graph.len()).map(|id| graph.name(id).chars().count()))
+        .max()
+        .unwrap_or(0);
+
+    let mut header = format!("{:>second$}", "Second");
+    for name in &schedule.workers {
+        header += &format!("   {name:^column$}");
+    }
+    println!("{header}   Done");
+
+    // where every worker is in its timeline, and how many tasks are done
+    let mut at = vec![0; schedule.workers.len()];
+    let mut done = 0;
+    for time in times {
+        let mut row = format!("{time:>second$}");
+        for (worker, spans) in timelines.iter().enumerate() {
+            while at[worker] < spans.len() && spans[at[worker]].end <= time {
+                at[worker] += 1;
+            }
+            let name = match spans.get(at[worker]).and_then(|span| span.task) {
+                Some(task) => graph.name(task),
+                None => ".",
+            };
+            row += &format!("   {name:^column$}");
+        }
+
+        while done < finished.len() && finished[done].end <= time {
+            done += 1;
+        }
+        let names = finished[..done]
+            .iter()
+            .map(|assignment| assignment.task)
+            .collect::<Vec<usize>>();
+        println!("{row}   {}", graph.names(&names));
+    }
+}
+
+/// One row per stretch of every worker's time: worker, whether it is working or idle, the task,
+/// start and end
+pub fn csv(graph: &Graph, schedule: &Schedule, path: &str) -> crate::Result<usize> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    let mut rows = 0;
+
+    writeln!(file, "worker,state,task,start,end")?;
+    for (worker, spans) in schedule.timelines().iter().enumerate() {
+        for span in spans {
+            let (state, task) = match span.task {
+                Some(task) => ("work", graph.name(task)),
+                None => ("idle", ""),
+            };
+            writeln!(
+                file,
+                "{},{},{},{},{}",
+                field(&schedule.workers[worker]),
+                state,
+                field(task),
+                span.start,
+                span.end
+            )?;
+            rows += 1;
+        }
+    }
+    file.flush()?;
+
+    Ok(rows)
+}
+
+/// A CSV field as RFC 4180 has it: quoted, with quotes doubled, when it holds a comma, a quote
+/// or a line break
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// svg layout, in pixels
+const LABELS: f64 = 90.0;
+const CHART: f64 = 1000.0;
+const ROW: f64 = 24.0;
+const MARGIN: f64 = 10.0;
+const AXIS: f64 = 30.0;
+
+/// A timeline with one row per worker and one bar per task, named when it fits. Hovering a bar
+/// shows the task with its start and end.
+pub fn svg(graph: &Graph, schedule: &Schedule, path: &str) -> crate::Result<usize> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    let mut bars = 0;
+
+    let width = LABELS + CHART + 2.0 * MARGIN;
+    let height = MARGIN + schedule.workers.len() as f64 * ROW + AXIS;
+    // pixels per second
+    let scale = if schedule.makespan == 0 {
+        0.0
+    } else {
+        CHART / schedule.makespan as f64
+    };
+    let x = |time: u64| LABELS + MARGIN + time as f64 * scale;
+
+    writeln!(
+        file,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="sans-serif" font-size="12">"#
+    )?;
+    writeln!(
+        file,
+        r##"<rect width="{width}" height="{height}" fill="#ffffff"/>"##
+    )?;
+
+    for (worker, spans) in schedule.timelines().iter().enumerate() {
+        let y = MARGIN + worker as f64 * ROW;
+        writeln!(
+            file,
+            r##"<rect x="{}" y="{y}" width="{CHART}" height="{}" fill="#eeeeee"/>"##,
+            x(0),
+            ROW - 4.0
+        )?;
+        writeln!(
+            file,
+            r#"<text x="{MARGIN}" y="{}">{}</text>"#,
+            y + ROW / 2.0 + 2.0,
+            escape(&schedule.workers[worker])
+        )?;
+
+        for span in spans {
+            let Some(task) = span.task else {
+                continue;
+            };
+            let name = escape(graph.name(task));
+            let (left, right) = (x(span.start), x(span.end));
+            writeln!(
+                file,
+                r##"<rect x="{left:.2}" y="{y}" width="{:.2}" height="{}" fill="{}" stroke="#ffffff" stroke-width="0.5"><title>{name}: {}-{}</title></rect>"##,
+                right - left,
+                ROW - 4.0,
+                color(task),
+                span.start,
+                span.end
+            )?;
+            // about 7 pixels per character
+            if right - left >= graph.name(task).chars().count() as f64 * 7.0 + 4.0 {
+                writeln!(
+                    file,
+                    r#"<text x="{:.2}" y="{}" text-anchor="middle">{name}</text>"#,
+                    (left + right) / 2.0,
+                    y + ROW / 2.0 + 2.0
+                )?;
+            }
+            bars += 1;
+        }
+    }
+
+    // time axis with a tick every 1, 2 or 5 times a power of ten seconds
+    let axis = MARGIN + schedule.workers.len() as f64 * ROW;
+    writeln!(
+        file,
+        r##"<line x1="{}" y1="{axis}" x2="{}" y2="{axis}" stroke="#000000"/>"##,
+        x(0),
+        x(schedule.makespan)
+    )?;
+    let step = tick_step(schedule.makespan);
+    for time in (0..=schedule.makespan).step_by(step as usize) {
+        writeln!(
+            file,
+            r##"<line x1="{0:.2}" y1="{axis}" x2="{0:.2}" y2="{1}" stroke="#000000"/>"##,
+            x(time),
+            axis + 4.0
+        )?;
+        writeln!(
+            file,
+            r#"<text x="{:.2}" y="{}" text-anchor="middle">{time}</text>"#,
+            x(time),
+            axis + 18.0
+        )?;
+    }
+    writeln!(file, "</svg>")?;
+    file.flush()?;
+
+    Ok(bars)
+}
+
+/// the smallest of 1, 2, 5, 10, 20... that leaves at most 10 ticks
+fn tick_step(makespan: u64) -> u64 {
+    let mut power = 1;
+    loop {
+        for step in [power, 2 * power, 5 * power] {
+            if makespan / step <= 10 {
+                return step;
+            }
+        }
+        power *= 10;
+    }
+}
+
+/// hues spread by the golden angle so tasks next to each other stand apart
+fn color(task: usize) -> String {
+    let hue = (task as f64 * 0.618_033_988_75).fract() * 360.0;
+    format!("hsl({hue:.0},60%,70%)")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
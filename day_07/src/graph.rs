@@ -0,0 +1,257 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+/// Tasks and the tasks that have to be done before them. Tasks are referred to by the index
+/// they got when they were first seen.
+#[derive(Debug, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    /// tasks that can only start once this one is done
+    dependents: Vec<Vec<usize>>,
+    /// tasks that have to be done before this one
+    requirements: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Read lines like the puzzle's `Step C must be finished before step A can begin.`, where
+    /// a step can be named by any word
+    pub fn parse(text: &str) -> crate::Result<Self> {
+        let mut graph = Graph::default();
+
+        for (idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (before, after) = line
+                .trim()
+                .strip_prefix("Step ")
+                .and_then(|rest| rest.strip_suffix(" can begin."))
+                .and_then(|rest| rest.split_once(" must be finished before step "))
+                .ok_or_else(|| {
+                    format!(
+                        "line {}: expected 'Step X must be finished before step Y can begin.'",
+                        idx + 1
+                    )
+                })?;
+            if before.contains(char::is_whitespace) || after.contains(char::is_whitespace) {
+                return Err(format!("line {}: step names cannot contain spaces", idx + 1).into());
+            }
+
+            let (before, after) = (graph.task(before), graph.task(after));
+            graph.depend(before, after);
+        }
+
+        Ok(graph)
+    }
+
+    /// the id of a task, added if it is new
+    pub fn task(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.dependents.push(Vec::new());
+        self.requirements.push(Vec::new());
+        id
+    }
+
+    /// `after` can only start once `before` is done
+    pub fn depend(&mut self, before: usize, after: usize) {
+        if !self.dependents[before].contains(&after) {
+            self.dependents[before].push(after);
+            self.requirements[after].push(before);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    /// The names of `ids` as one string: the puzzle's single letter steps read as one word,
+    /// longer names need a space
+    pub fn names(&self, ids: &[usize]) -> String {
+        let names = ids.iter().map(|&id| self.name(id)).collect::<Vec<&str>>();
+        if names.iter().all(|name| name.chars().count() == 1) {
+            names.concat()
+        } else {
+            names.join(" ")
+        }
+    }
+
+    pub fn dependents(&self, id: usize) -> &[usize] {
+        &self.dependents[id]
+    }
+
+    pub fn requirements(&self, id: usize) -> &[usize] {
+        &self.requirements[id]
+    }
+
+    /// Position of every task in tie-breaking order: higher priority first, then by name.
+    /// Tasks without a priority have 0, so with no priorities at all this is the puzzle's
+    /// alphabetical order.
+    pub fn ranks(&self, priorities: &HashMap<String, i64>) -> Vec<usize> {
+        let mut sorted = (0..self.len()).collect::<Vec<usize>>();
+        sorted.sort_by_key(|&id| {
+            let priority = priorities.get(self.name(id)).copied().unwrap_or(0);
+            (Reverse(priority), self.name(id))
+        });
+
+        let mut ranks = vec![0; self.len()];
+        for (rank, id) in sorted.into_iter().enumerate() {
+            ranks[id] = rank;
+        }
+        ranks
+    }
+
+    /// Every task once, each after all of its requirements. When several tasks could go next
+    /// the one ranked first goes.
+    pub fn order(&self, ranks: &[usize]) -> Result<Vec<usize>, Cycle> {
+        let mut pending = self
+            .requirements
+            .iter()
+            .map(|requirements| requirements.len())
+            .collect::<Vec<usize>>();
+        let mut ready = Ready::new(ranks);
+        for id in (0..self.len()).filter(|&id| pending[id] == 0) {
+            ready.push(id);
+        }
+
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            for &next in &self.dependents[id] {
+                pending[next] -= 1;
+                if pending[next] == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+
+        if order.len() < self.len() {
+            return Err(self.cycle().expect("tasks left over without a cycle"));
+        }
+        Ok(order)
+    }
+
+    /// fails with a cycle if the tasks can never all be done
+    pub fn check(&self) -> Result<(), Cycle> {
+        match self.cycle() {
+            Some(cycle) => Err(cycle),
+            None => Ok(()),
+        }
+    }
+
+    /// A cycle of tasks each waiting for the next, found with a depth first search that looks
+    /// for a requirement still on the stack
+    fn cycle(&self) -> Option<Cycle> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            OnStack,
+            Done,
+        }
+
+        let mut state = vec![State::New; self.len()];
+        for start in 0..self.len() {
+            if state[start] != State::New {
+                continue;
+            }
+
+            // (task, index of the next dependent to visit)
+            let mut stack = vec![(start, 0)];
+            state[start] = State::OnStack;
+            while let Some(top) = stack.last_mut() {
+                let id = top.0;
+                let Some(&dependent) = self.dependents[id].get(top.1) else {
+                    state[id] = State::Done;
+                    stack.pop();
+                    continue;
+                };
+                top.1 += 1;
+
+                match state[dependent] {
+                    State::New => {
+                        state[dependent] = State::OnStack;
+                        stack.push((dependent, 0));
+                    }
+                    State::OnStack => {
+                        let from = stack.iter().position(|&(id, _)| id == dependent).unwrap();
+                        let mut path = stack[from..]
+                            .iter()
+                            .map(|&(id, _)| self.name(id).to_string())
+                            .collect::<Vec<String>>();
+                        path.push(self.name(dependent).to_string());
+                        return Some(Cycle(path));
+                    }
+                    State::Done => {}
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Tasks in a cycle, the first one repeated at the end
+#[derive(Debug)]
+pub struct Cycle(pub Vec<String>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dependency cycle: {}", self.0.join(" -> "))
+    }
+}
+
+impl std::error::Error for Cycle {}
+
+/// Tasks whose requirements are all done, handed out in rank order
+pub struct Ready<'a> {
+    ranks: &'a [usize],
+    heap: BinaryHeap<Reverse<(usize, usize)>>,
+}
+
+impl<'a> Ready<'a> {
+    pub fn new(ranks: &'a [usize]) -> Self {
+        Ready {
+            ranks,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, id: usize) {
+        self.heap.push(Reverse((self.ranks[id], id)));
+    }
+
+    pub fn pop(&mut self) -> Option<usize> {
+        self.heap.pop().map(|Reverse((_, id))| id)
+    }
+}
+
+/// Read priorities, one `name priority` per line. Blank lines and lines starting with `#` are
+/// skipped.
+pub fn parse_priorities(text: &str) -> crate::Result<HashMap<String, i64>> {
+    let mut priorities = HashMap::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, priority) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("line {}: expected 'name priority'", idx + 1))?;
+        let priority = priority
+            .trim()
+            .parse::<i64>()
+            .map_err(|e| format!("line {}: {}", idx + 1, e))?;
+        priorities.insert(name.to_string(), priority);
+    }
+    Ok(priorities)
+}
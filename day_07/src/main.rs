@@ -1,257 +1,240 @@
-use std::collections::{HashMap, HashSet};
-use std::{str::FromStr, string};
+use clap::Parser;
+use critical::Critical;
+use durations::Durations;
+use fleet::{Policy, Worker};
+use graph::Graph;
+use std::collections::HashMap;
+
+mod critical;
+mod durations;
+mod fleet;
+mod gantt;
+mod graph;
+mod schedule;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn main() -> Result<()> {
-    let args = std::env::args().collect::<Vec<String>>();
-    if args.len() != 2 {
-        eprintln!("usage: {} <input-file>", args[0]);
-        std::process::exit(1);
-    }
-    let data = std::fs::read_to_string(&args[1])?;
+#[derive(Parser, Debug)]
+struct Args {
+    /// Name of input file
+    input: String,
+
+    /// Number of workers in part 2, all alike
+    #[arg(long, default_value_t = 5)]
+    workers: usize,
+
+    /// File with one `name speed skill...` per line describing the workers in part 2 instead.
+    /// A worker with speed 2 takes half the time; it can only do tasks whose skills it all has.
+    #[arg(long, conflicts_with = "workers")]
+    fleet: Option<String>,
+
+    /// File with one `name skill...` per line, for tasks only some workers can do
+    #[arg(long)]
+    skills: Option<String>,
+
+    /// Which ready task an idle worker gets
+    #[arg(long, default_value = "alphabetical", value_parser = fleet::parse_policy)]
+    policy: String,
+
+    /// File with one `name priority` per line. Among tasks that are ready at the same time the
+    /// highest priority goes first, then the alphabetically first.
+    #[arg(long)]
+    priorities: Option<String>,
+
+    /// Seconds every task takes on top of its cost
+    #[arg(long, default_value_t = 60)]
+    base: u64,
+
+    /// Seconds per unit of cost, rounded up
+    #[arg(long, default_value_t = 1.0, value_parser = durations::parse_scale)]
+    scale: f64,
+
+    /// File with one `name cost` per line, for tasks that do not cost their letter's position
+    /// in the alphabet
+    #[arg(long)]
+    durations: Option<String>,
+
+    /// Print what every worker does in part 2, like the puzzle's table
+    #[arg(long)]
+    gantt: bool,
+
+    /// Write every worker's tasks and idle time in part 2 to this CSV file
+    #[arg(long)]
+    csv: Option<String>,
+
+    /// Draw the part 2 schedule as a timeline in this SVG file
+    #[arg(long)]
+    svg: Option<String>,
+
+    /// Print the critical path and the slack of every task with each task done by the fastest
+    /// worker that can do it, the least time the workers could take and, without --fleet, the
+    /// fewest workers that finish within the critical path
+    #[arg(long)]
+    critical: bool,
+}
 
-    let mut conditions: Vec<Pre> = Vec::new();
-    for line in data.lines() {
-        conditions.push(Pre::from_str(line).unwrap());
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let data = std::fs::read_to_string(&args.input)?;
+    let graph = Graph::parse(&data).map_err(|e| format!("{}: {}", args.input, e))?;
+
+    let priorities = match &args.priorities {
+        Some(path) => graph::parse_priorities(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path, e))?,
+        None => HashMap::new(),
+    };
+    let ranks = graph.ranks(&priorities);
+
+    let mut durations = Durations::new(args.base, args.scale, durations::letter);
+    if let Some(path) = &args.durations {
+        durations
+            .read_overrides(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path, e))?;
     }
 
-    let shift_size = 5;
-    part1(&conditions).unwrap();
-    part2(&conditions, shift_size).unwrap();
+    let fleet = match &args.fleet {
+        Some(path) => fleet::parse_fleet(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path, e))?,
+        None => Worker::plain(args.workers),
+    };
+    let skills = match &args.skills {
+        Some(path) => fleet::parse_skills(&std::fs::read_to_string(path)?)?,
+        None => HashMap::new(),
+    };
+    let policy = fleet::policy(&args.policy)?;
+
+    part1(&graph, &ranks)?;
+    part2(
+        &args,
+        &graph,
+        &ranks,
+        &durations,
+        &fleet,
+        &skills,
+        policy.as_ref(),
+    )?;
 
     Ok(())
 }
 
-fn part1(conditions: &Vec<Pre>) -> Result<()> {
-    /* 
-     * a hashmap b/w a characters(task) and all the tasks that need to be done before it that has
-     * not been done yet.
-     * */
-    let mut conditions_map: HashMap<char, HashSet<char>> = HashMap::new();
-    for condition in conditions {
-        conditions_map
-            .entry(condition.1)
-            .or_insert(HashSet::new())
-            .insert(condition.0);
-        // make sure that all the inpur characters are present in conditions_map. Something mapping
-        // to an empty hashset is just a task that has no pending dependencies
-        conditions_map.entry(condition.0).or_insert(HashSet::new());
-    }
-
-    // gather every task that has all its pre-reqs completed
-    let mut ready: HashSet<char> = HashSet::new();
-    for (&node, requirements) in &conditions_map {
-        if requirements.is_empty() {
-            ready.insert(node);
-        }
-    }
-
-    let mut order = String::new();
-
-    loop {
-        // until nothing remains to do
-        if ready.is_empty() {
-            break;
-        }
-
-        let first = get_first(&ready).unwrap();
-        // complete a task...remove it from pending dependencies
-        conditions_map.iter_mut().for_each(|(_, requirements)| {
-            if requirements.contains(&first) {
-                requirements.remove(&first);
-            }
-        });
-
-        conditions_map.remove(&first).unwrap(); // remove completed nodes as its requirements are
-                                                // done and it will interfere in dertemining what
-                                                // else should get into the ready set.
-
-        ready.remove(&first); // get rid of completed nodes
-
-        // update 'ready'
-        for (&node, requirements) in &conditions_map {
-            if !ready.contains(&node) && requirements.is_empty() {
-                ready.insert(node);
-            }
-        }
-
-        order.push(first);
-    }
-
-    println!("{order}");
+fn part1(graph: &Graph, ranks: &[usize]) -> Result<()> {
+    let order = graph.order(ranks).map_err(|cycle| cycle.to_string())?;
+    println!("{}", graph.names(&order));
 
     Ok(())
 }
 
-struct Work {
-    task: char,
-    time_spent: i32,
-}
-
-fn part2(conditions: &Vec<Pre>, shift_size: usize) -> Result<()> {
-    let mut conditions_map: HashMap<char, HashSet<char>> = HashMap::new();
-    for condition in conditions {
-        conditions_map
-            .entry(condition.1)
-            .or_insert(HashSet::new())
-            .insert(condition.0);
-        // make sure that all the inpur characters are present in conditions_map
-        conditions_map.entry(condition.0).or_insert(HashSet::new());
+fn part2(
+    args: &Args,
+    graph: &Graph,
+    ranks: &[usize],
+    durations: &Durations,
+    fleet: &[Worker],
+    skills: &HashMap<String, Vec<String>>,
+    policy: &dyn Policy,
+) -> Result<()> {
+    graph.check().map_err(|cycle| cycle.to_string())?;
+    if fleet.is_empty() && graph.len() > 0 {
+        return Err("no workers to do the tasks".into());
     }
+    let durations = durations.all(graph)?;
+    let needs = fleet::needs(graph, skills, fleet)?;
 
-    let mut ready: HashSet<char> = HashSet::new();
-    for (&node, requirements) in &conditions_map {
-        if requirements.is_empty() {
-            ready.insert(node);
-        }
+    let schedule = schedule::simulate(graph, ranks, &durations, &needs, fleet, policy);
+    if args.gantt {
+        gantt::table(graph, &schedule);
     }
+    println!("{}", schedule.makespan);
 
-    for task in &ready {
-        conditions_map.remove(task).unwrap();
+    if let Some(path) = &args.csv {
+        let rows = gantt::csv(graph, &schedule, path)?;
+        println!("Successfully written {rows} spans to {path}");
     }
-
-    let mut time = 0;
-    let mut factory: Vec<Option<Work>> = Vec::new();
-    factory.resize_with(shift_size, || None);
-
-    assign_work(&mut factory, &mut ready);
-
-    loop {
-        let mut is_working = false;
-        for work in &factory {
-            if work.is_some() {
-                is_working = true;
-            }
-        }
-
-        if !is_working {
-            break;
-        }
-
-        time += 1;
-        let completed = tick(&mut factory);
-        if completed.is_some() {
-            // something completed
-            for task in &completed.unwrap() {
-                // update requirements set for each task
-                conditions_map.iter_mut().for_each(|(_, requirements)| {
-                    requirements.remove(task);
-                })
-            }
-
-            // collect newly ready tasks
-            let mut ready_temp = HashSet::new();
-            for (&task, requirements) in &conditions_map {
-                if requirements.is_empty() {
-                    ready_temp.insert(task);
-                }
-            }
-
-            // remove ready tasks from collections_map so that they dont appear in next search of
-            // ready tasks, and put them in ready set
-            for &task in &ready_temp {
-                conditions_map.remove(&task).unwrap();
-                ready.insert(task);
-            }
-
-            // assign work to idle workers
-            assign_work(&mut factory, &mut ready);
-        }
+    if let Some(path) = &args.svg {
+        let bars = gantt::svg(graph, &schedule, path)?;
+        println!("Successfully written {bars} tasks to {path}");
     }
 
-    println!("{time}");
+    if args.critical {
+        print_critical(
+            graph,
+            ranks,
+            &durations,
+            &needs,
+            fleet,
+            args.fleet.is_some(),
+            policy,
+        )?;
+    }
 
     Ok(())
 }
 
-/* 
- * Run one step of the simulation. If any worker has completed its job then mark it as idle and
- * return the list of completed jobs.
- * */
-fn tick(factory: &mut Vec<Option<Work>>) -> Option<Vec<char>> {
-    let mut completed = Vec::new();
-    for work in factory {
-        let mut task_completed = false;
-        work.as_mut().map(|w| {
-            w.time_spent += 1;
-
-            // nodes should be uppercase
-            if w.time_spent == (60 + (w.task as u8 - 'A' as u8 + 1u8) as i32) {
-                task_completed = true;
-            }
-
-            // for testinput
-            /* if w.time_spent == (w.task as u8 - 'A' as u8 + 1u8) as i32 {
-                task_completed = true;
-            } */
-        });
-
-        if task_completed {
-            completed.push(work.as_mut().unwrap().task);
-            *work = None;
-        }
-    }
-
-    if completed.is_empty() {
-        None
+fn print_critical(
+    graph: &Graph,
+    ranks: &[usize],
+    durations: &[u64],
+    needs: &[Vec<String>],
+    fleet: &[Worker],
+    mixed: bool,
+    policy: &dyn Policy,
+) -> Result<()> {
+    let order = graph.order(ranks).map_err(|cycle| cycle.to_string())?;
+
+    // every task done by the fastest worker that can do it, which for plain workers is its
+    // duration
+    let fastest = (0..graph.len())
+        .map(|id| {
+            fleet
+                .iter()
+                .filter(|worker| worker.can_do(&needs[id]))
+                .map(|worker| worker.time(durations[id]))
+                .min()
+                .unwrap()
+        })
+        .collect::<Vec<u64>>();
+    let capacity = fleet.iter().map(|worker| worker.speed).sum::<f64>();
+    let critical = Critical::new(graph, &order, &fastest);
+    let bound = critical.lower_bound(durations, capacity);
+
+    let path = critical
+        .path
+        .iter()
+        .map(|&id| graph.name(id))
+        .collect::<Vec<&str>>();
+    println!(
+        "critical path: {} ({} seconds)",
+        path.join(" -> "),
+        critical.length
+    );
+    println!("lower bound with {} workers: {}", fleet.len(), bound);
+    // the sweep adds plain workers, which says nothing about a fleet of mixed ones
+    if !mixed {
+        println!(
+            "fewest workers done in {} seconds: {}",
+            critical.length,
+            critical::minimum_workers(graph, ranks, durations, &critical, policy)
+        );
     } else {
-        Some(completed)
+        println!("fewest workers: only worked out for --workers, not a --fleet");
     }
-}
 
-/* 
- * go through every worker and if any is idle then look for any ready pending work an assign it to that
- * worker. Remove the assigned tasks from the set of ready pending works
- * */
-fn assign_work(factory: &mut Vec<Option<Work>>, ready: &mut HashSet<char>) {
-    for work in factory {
-        if work.is_none() {
-            // someone is idle
-            let task = get_first(&ready);
-            if task.is_some() {
-                // ...we got something to do
-                ready.remove(&task.unwrap());
-                *work = Some(Work {
-                    // initialize the worker
-                    task: task.unwrap(),
-                    time_spent: 0,
-                });
-            }
-        }
+    let width = order
+        .iter()
+        .map(|&id| graph.name(id).chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Task".len());
+    println!("{:<width$}  Earliest    Latest     Slack", "Task");
+    for &id in &order {
+        println!(
+            "{:<width$}  {:>8}  {:>8}  {:>8}",
+            graph.name(id),
+            critical.earliest[id],
+            critical.latest[id],
+            critical.slack(id)
+        );
     }
-}
 
-fn get_first(set: &HashSet<char>) -> Option<char> {
-    if set.is_empty() {
-        None
-    } else {
-        set.into_iter()
-            .reduce(|a, b| if *a < *b { a } else { b })
-            .copied()
-    }
-}
-
-#[derive(Debug)]
-struct Pre(char, char);
-
-impl FromStr for Pre {
-    type Err = string::ParseError;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let character_substrings = s
-            .split(" ")
-            .filter(|&token| token.len() == 1)
-            .map(|sub_str| char::from_str(sub_str).unwrap())
-            .collect::<Vec<char>>();
-        let first = character_substrings
-            .get(0)
-            .expect("could not get substring containing first character");
-
-        let second = character_substrings
-            .get(1)
-            .expect("could not get substring containing second character");
-
-        Ok(Pre(*first, *second))
-    }
+    Ok(())
 }
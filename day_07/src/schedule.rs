@@ -0,0 +1,167 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap};
+
+use crate::fleet::{Policy, Worker};
+use crate::graph::Graph;
+
+/// One task done by one worker, from `start` up to but not including `end`
+#[derive(Debug, Clone, Copy)]
+pub struct Assignment {
+    pub worker: usize,
+    pub task: usize,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Who did what when
+#[derive(Debug)]
+pub struct Schedule {
+    /// names of the workers
+    pub workers: Vec<String>,
+    /// seconds until every task is done
+    pub makespan: u64,
+    /// in the order the tasks were handed out
+    pub assignments: Vec<Assignment>,
+}
+
+/// A stretch of one worker's time, `None` while idle
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub task: Option<usize>,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Schedule {
+    /// Everything every worker did from 0 to the makespan, idle gaps included
+    pub fn timelines(&self) -> Vec<Vec<Span>> {
+        let mut timelines = vec![Vec::new(); self.workers.len()];
+        let mut times = vec![0; self.workers.len()];
+        // a worker only gets a new task once the last one is done, so these are in order
+        for assignment in &self.assignments {
+            let (spans, time) = (
+                &mut timelines[assignment.worker],
+                &mut times[assignment.worker],
+            );
+            if assignment.start > *time {
+                spans.push(Span {
+                    task: None,
+                    start: *time,
+                    end: assignment.start,
+                });
+            }
+            spans.push(Span {
+                task: Some(assignment.task),
+                start: assignment.start,
+                end: assignment.end,
+            });
+            *time = assignment.end;
+        }
+        for (spans, time) in timelines.iter_mut().zip(times) {
+            if time < self.makespan {
+                spans.push(Span {
+                    task: None,
+                    start: time,
+                    end: self.makespan,
+                });
+            }
+        }
+        timelines
+    }
+}
+
+/// Run every task with the workers of `fleet`. Whenever workers are idle they are handed tasks
+/// in their own order, each the first ready task it can do in the order of `policy`. `needs`
+/// has the skills every task needs, when there are any.
+///
+/// Instead of ticking through every second this jumps from one completion to the next, keeping
+/// the running tasks in a queue by the time they end, so the cost only depends on the number of
+/// tasks and not on how long they take. The graph has to be free of cycles and every task has to
+/// be doable by some worker.
+pub fn simulate(
+    graph: &Graph,
+    ranks: &[usize],
+    durations: &[u64],
+    needs: &[Vec<String>],
+    fleet: &[Worker],
+    policy: &dyn Policy,
+) -> Schedule {
+    let needs = |task: usize| needs.get(task).map_or(&[][..], |needs| needs.as_slice());
+
+    // number of requirements of every task that are not done yet
+    let mut pending = (0..graph.len())
+        .map(|id| graph.requirements(id).len())
+        .collect::<Vec<usize>>();
+
+    // (key, task) of every ready task, the first one goes first
+    let mut ready: BTreeSet<((u64, usize), usize)> = BTreeSet::new();
+    let key = |task: usize| (policy.key(durations[task], ranks[task]), task);
+    for id in (0..graph.len()).filter(|&id| pending[id] == 0) {
+        ready.insert(key(id));
+    }
+
+    let mut idle = (0..fleet.len()).collect::<BTreeSet<usize>>();
+    // (end, worker, task) of every running task
+    let mut running: BinaryHeap<Reverse<(u64, usize, usize)>> = BinaryHeap::new();
+
+    let mut assignments = Vec::with_capacity(graph.len());
+    let mut time = 0;
+    loop {
+        // assign work to idle workers
+        let mut busy = Vec::new();
+        for &worker in &idle {
+            if ready.is_empty() {
+                break;
+            }
+            let Some(entry) = ready
+                .iter()
+                .find(|&&(_, task)| fleet[worker].can_do(needs(task)))
+                .copied()
+            else {
+                continue;
+            };
+            ready.remove(&entry);
+            busy.push(worker);
+
+            let task = entry.1;
+            let end = time + fleet[worker].time(durations[task]);
+            running.push(Reverse((end, worker, task)));
+            assignments.push(Assignment {
+                worker,
+                task,
+                start: time,
+                end,
+            });
+        }
+        for worker in busy {
+            idle.remove(&worker);
+        }
+
+        let Some(&Reverse((end, _, _))) = running.peek() else {
+            break;
+        };
+        time = end;
+
+        // everything done at this second frees its worker, and tasks whose last requirement got
+        // done are ready
+        while let Some(&Reverse((end, worker, task))) = running.peek() {
+            if end != time {
+                break;
+            }
+            running.pop();
+            idle.insert(worker);
+            for &next in graph.dependents(task) {
+                pending[next] -= 1;
+                if pending[next] == 0 {
+                    ready.insert(key(next));
+                }
+            }
+        }
+    }
+
+    Schedule {
+        workers: fleet.iter().map(|worker| worker.name.clone()).collect(),
+        makespan: time,
+        assignments,
+    }
+}